@@ -1,22 +1,30 @@
 use cosmwasm_std::{
-    entry_point, to_binary, Coin, Deps, DepsMut, Env, MessageInfo, Response,from_binary,
-    StdResult, Uint128,CosmosMsg,WasmMsg,Decimal,BankMsg,Storage, Timestamp
+    entry_point, to_binary, to_json_binary, Coin, Deps, DepsMut, Env, Event, MessageInfo, Reply, Response, SubMsg,from_binary, from_json,
+    StdResult, StdError, Uint128,CosmosMsg,WasmMsg,Decimal,BankMsg,Storage, Timestamp, IbcTimeout, Binary
 };
+use cw_storage_plus::Bound;
 
 use cw2::set_contract_version;
 use cw20::{ Cw20ExecuteMsg,Cw20ReceiveMsg};
 use cw721::{Cw721ReceiveMsg, Cw721ExecuteMsg};
 
 use crate::error::{ContractError};
-use crate::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg,SellNft, BuyNft};
+use crate::events;
+use crate::msg::{ ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,SellNft, BuyNft, AskSetting, UpdateAskMsg, MakeCounterOfferMsg, BuyCw1155Msg, SetCw1155BidMsg,
+    MarketplaceHookMsg, SaleHookMsg, AskHookMsg, BidHookMsg, CollectionBidHookMsg, OfferHookMsg, Cw1155AskHookMsg, Cw1155BidHookMsg, TokenHookMsg, NftHookMsg, ReceiveMsg, Cw1155HookMsg};
 use crate::query::query_bids;
 use crate::state::{
-    State,CONFIG,Asset,UserInfo, MEMBERS,SaleInfo, COLLECTIONINFO, CollectionInfo, TOKENADDRESS,  TvlInfo, COINDENOM, SaleType
+    State,CONFIG,Asset,UserInfo, MEMBERS,SaleInfo, COLLECTIONINFO, CollectionInfo, TOKENADDRESS,  TvlInfo, COINDENOM, SaleType,
+    CollectionStats, COLLECTION_STATS, VOLUME_BUCKETS, VOLUME_BUCKET_SECONDS, DAY_SECONDS, Candle, CandleInterval,
+    SALE_SEQ, legacy_sale_history, Activity, ActivityType, activities, ACTIVITY_SEQ, USER_ACTIVITIES, HookType,
+    RewardConfig, REWARD_CONFIG, REWARD_POOL, REWARD_OUTSTANDING, REWARD_RATES, EPOCH_REWARDS, FINDER_EARNINGS, CLEANUP_BOUNTY, CLEANUP_POOL, CLEANUP_GRACE_SECONDS, CLEANUP_BOUNTY_SHARE,
+    ExpiryBackfill, ExpiryBackfillBook, EXPIRY_BACKFILL
 };
 use crate::state::{
-    Ask,asks,AskKey,ask_key,Order,Bid, bids, BidKey, bid_key, sale_history, sale_history_key, tvl_key, tvl, collection_bid_key, collection_bids, CollectionBid
+    Ask,asks,AskKey,ask_key,Order,Bid, bids, BidKey, bid_key, sale_history, sale_history_key, tvl_key, tvl, collection_bid_key, collection_bids, CollectionBid,
+    CounterOffer, counter_offers, Offer, offers, NftStandard, Cw1155Ask, cw1155_asks, Cw1155Bid, cw1155_bids, Ics721Origin
 };
-use crate::package::{QueryOfferingsResult};
+use crate::package::{QueryOfferingsResult, Cw1155ReceiveMsg, Cw1155ExecuteMsg, Ics721QueryMsg, Ics721OutgoingMsg};
 
 
 const CONTRACT_NAME: &str = "Hope_Market_Place";
@@ -24,6 +32,13 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const MAX_QUERY_LIMIT: u32 = 30;
 
+const HOOK_REPLY_ID: u64 = 1;
+// Gas each hook callback may use, so a misbehaving hook can not exhaust the trade's gas
+const HOOK_GAS_LIMIT: u64 = 500_000;
+
+/// IBC timeout for NFTs sent back to their origin chain
+const ICS721_TIMEOUT_SECONDS: u64 = 600;
+
 #[entry_point]
 pub fn instantiate(
     deps: DepsMut,
@@ -38,7 +53,29 @@ pub fn instantiate(
         admin: msg.admin
     };
     CONFIG.save(deps.storage,&state)?;
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_event(Event::new("contract-instantiated")
+            .add_attribute("owner", state.owner)
+            .add_attribute("admin", state.admin)))
+}
+
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_event(Event::new("contract-migrated").add_attribute("version", CONTRACT_VERSION)))
+}
+
+/// Hook callbacks are sent with `reply_on_error`, so a failing hook lands here and the trade still goes through
+#[entry_point]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        HOOK_REPLY_ID => Ok(Response::new()
+            .add_attribute("action", "hook failed")
+            .add_attribute("error", msg.result.unwrap_err())),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
 }
 
 #[entry_point]
@@ -62,6 +99,7 @@ pub fn execute(
             token_id,
             list_price,
             token_address),
+        ExecuteMsg::UpdateAsk(msg) => execute_update_ask(deps, env, info, msg),
         ExecuteMsg::WithdrawNft {
             nft_address,
             token_id 
@@ -71,17 +109,20 @@ pub fn execute(
             info,
             nft_address,
             token_id),
-        ExecuteMsg::Receive(msg) =>execute_receive(
+        ExecuteMsg::Receive(ReceiveMsg::Cw20(msg)) =>execute_receive(
             deps,
             env,
             info,
             msg),
+        ExecuteMsg::Receive(ReceiveMsg::Cw1155(msg)) => execute_receive_cw1155(deps, env, info, msg),
         ExecuteMsg::SetBidCoin { 
             nft_address, 
             expire, 
             sale_type, 
             token_id, 
-            list_price 
+            list_price,
+            finder,
+            origin_receiver
         } => execute_bid_with_coin(
             deps, 
             env, 
@@ -90,7 +131,14 @@ pub fn execute(
             token_id, 
             expire, 
             sale_type, 
-            list_price),
+            list_price,
+            finder,
+            origin_receiver),
+        ExecuteMsg::UpdateBid { 
+            nft_address, 
+            token_id, 
+            expire 
+        } => execute_update_bid_with_coin(deps, env, info, nft_address, token_id, expire),
         ExecuteMsg::RemoveBid { 
             nft_address, 
             token_id 
@@ -133,6 +181,14 @@ pub fn execute(
             token_id,
             bidder
         ),
+        ExecuteMsg::MakeCounterOffer(msg) => execute_make_counter_offer(deps, env, info, msg),
+        ExecuteMsg::RemoveCounterOffer { nft_address, token_id, bidder } => execute_remove_counter_offer(deps, env, info, nft_address, token_id, bidder),
+        ExecuteMsg::AcceptCounterOffer { nft_address, token_id } => execute_accept_counter_offer_with_coin(deps, env, info, nft_address, token_id),
+        ExecuteMsg::RemoveOffer { nft_address, token_id } => execute_remove_offer(deps, env, info, nft_address, token_id),
+        ExecuteMsg::BuyCw1155(msg) => execute_buy_cw1155_with_coin(deps, env, info, msg),
+        ExecuteMsg::SetCw1155Bid(msg) => execute_set_cw1155_bid_with_coin(deps, env, info, msg),
+        ExecuteMsg::RemoveCw1155Bid { nft_address, token_id } => execute_remove_cw1155_bid(deps, env, info, nft_address, token_id),
+        ExecuteMsg::WithdrawCw1155 { nft_address, token_id } => execute_withdraw_cw1155(deps, env, info, nft_address, token_id),
         ExecuteMsg::AddTokenAddress {
              symbol, 
              address 
@@ -165,6 +221,68 @@ pub fn execute(
           info,
           address
         ),
+        ExecuteMsg::MigrateSaleHistory { 
+          limit 
+        } => execute_migrate_sale_history(
+          deps,
+          env,
+          info,
+          limit
+        ),
+        ExecuteMsg::BackfillExpiryIndexes { 
+          limit 
+        } => execute_backfill_expiry_indexes(
+          deps,
+          env,
+          info,
+          limit
+        ),
+        ExecuteMsg::AddHook { 
+          hook_type,
+          address 
+        } => execute_add_hook(
+          deps,
+          env,
+          info,
+          hook_type,
+          address
+        ),
+        ExecuteMsg::RemoveHook { 
+          hook_type,
+          address 
+        } => execute_remove_hook(
+          deps,
+          env,
+          info,
+          hook_type,
+          address
+        ),
+        ExecuteMsg::SetRewardConfig { 
+          token_address,
+          epoch_seconds 
+        } => execute_set_reward_config(
+          deps,
+          env,
+          info,
+          token_address,
+          epoch_seconds
+        ),
+        ExecuteMsg::SetRewardRate { 
+          collection,
+          denom,
+          rate 
+        } => execute_set_reward_rate(
+          deps,
+          env,
+          info,
+          collection,
+          denom,
+          rate
+        ),
+        ExecuteMsg::ClaimRewards {} => execute_claim_rewards(deps, env, info),
+        ExecuteMsg::CleanupExpired { limit } => execute_cleanup_expired(deps, env, info, limit),
+        ExecuteMsg::SetCleanupBounty { bounty } => execute_set_cleanup_bounty(deps, env, info, bounty),
+        ExecuteMsg::FundCleanupBounty {} => execute_fund_cleanup_bounty(deps, env, info),
         ExecuteMsg::AddCollection { 
             royalty_portion, 
             members,
@@ -181,6 +299,11 @@ pub fn execute(
             members,
             nft_address 
         } =>execute_update_collection(deps,env,info,royalty_portion,members,nft_address),
+        ExecuteMsg::SetFinderFee { nft_address, finder_fee } => execute_set_finder_fee(deps, env, info, nft_address, finder_fee),
+        ExecuteMsg::SetMinBidIncrement { nft_address, min_bid_increment } => execute_set_min_bid_increment(deps, env, info, nft_address, min_bid_increment),
+        ExecuteMsg::SetMinBidPortion { nft_address, min_bid_portion } => execute_set_min_bid_portion(deps, env, info, nft_address, min_bid_portion),
+        ExecuteMsg::SetNftStandard { nft_address, standard } => execute_set_nft_standard(deps, env, info, nft_address, standard),
+        ExecuteMsg::SetIcs721Bridge { nft_address, bridge } => execute_set_ics721_bridge(deps, env, info, nft_address, bridge),
         ExecuteMsg::FixNft{address,token_id} =>execute_fix_nft(deps,env,info,address,token_id),
         ExecuteMsg::SetOfferings { address, offering }=>execute_set_offerings(deps,env,info,address,offering),
         ExecuteMsg::SetTvl { address, tvl } =>execute_set_tvl(deps,env,info,address,tvl),
@@ -208,38 +331,31 @@ fn execute_receive_nft(
     if collection_info.is_none() {
         return Err(ContractError::WrongNFTContractError { });
     }
+    if collection_info.as_ref().unwrap().standard != NftStandard::Cw721 {
+        return Err(ContractError::WrongNFTContractError { });
+    }
+
+    //Sends that are not listings, like accepting an offer
+    if from_json::<SellNft>(&rcv_msg.msg).is_err() {
+        if let Ok(hook_msg) = from_json::<NftHookMsg>(&rcv_msg.msg) {
+            return execute_nft_hook(deps, env, info, rcv_msg, collection_info.unwrap(), hook_msg);
+        }
+    }
 
     let msg:SellNft = from_binary(&rcv_msg.msg)?;
     let nft_address = info.sender.to_string();
+    if let Some(reserved_for) = &msg.reserved_for {
+        deps.api.addr_validate(reserved_for)?;
+    }
+    if let Some(buy_now_price) = msg.buy_now_price {
+        if buy_now_price < msg.list_price.amount || buy_now_price < msg.reserve_price.unwrap_or_default() {
+            return Err(ContractError::WrongConfig {  })
+        }
+    }
     let token_address = msg.token_address;
 
     //Coin and Token validation
-    if token_address.is_none(){
-        match COINDENOM.may_load(deps.storage, &msg.list_price.denom)?{
-            Some(denom) =>{
-               if !denom{
-                return Err(ContractError::WrongCoinDenom {  })
-               }
-            },
-            None =>{
-                return Err(ContractError::WrongCoinDenom {  })
-            }
-        }
-    } else{
-        //Validate Configuration, token_address can not be existed if the list price is set as coin
-        let token_address = token_address.unwrap();
-        let token_denom = TOKENADDRESS.may_load(deps.storage, &token_address)?;
-        match  token_denom{
-          Some(denom) =>{
-            if denom != msg.list_price.denom{
-                return Err(ContractError::WrongTokenContractError {  })
-            }
-          }
-          None =>{
-                return Err(ContractError::WrongTokenContractError {  })
-           }
-        }   
-    }
+    validate_list_price(deps.storage, &msg.list_price, &token_address)?;
 
     //Save ask
     let ask = Ask {
@@ -248,15 +364,66 @@ fn execute_receive_nft(
         list_price: msg.list_price.clone(),
         expires_at: msg.expire,
         collection: nft_address,
+        reserved_for: msg.reserved_for,
+        buy_now_price: msg.buy_now_price,
+        reserve_price: msg.reserve_price,
+        min_bid_increment: msg.min_bid_increment,
     };
 
     if ask.is_expired(&env.block){
         return Err(ContractError::AskExpired {  })
     }
 
+    //Instant sell to the best collection bid at or above the list price
+    if msg.instant_sell == Some(true) {
+        let best_bid = find_best_collection_bid(deps.storage, &env, &ask, &token_address)?;
+        if let Some(bid) = best_bid {
+            let mut messages: Vec<CosmosMsg> = Vec::new();
+            let sale_event = events::sale(&ask.collection, &ask.token_id, &ask.seller, &bid.bidder, &bid.list_price, "collection_bid");
+            let hook_msgs = settle_collection_bid(
+                deps,
+                env,
+                info,
+                bid.clone(),
+                ask.seller.clone(),
+                ask.token_id.clone(),
+                &mut messages
+            )?;
+
+            return Ok(Response::new()
+                .add_attribute("action", "Instant sell to collection bid")
+                .add_attribute("token_id", ask.token_id)
+                .add_attribute("seller", ask.seller)
+                .add_attribute("bidder", bid.bidder)
+                .add_attribute("denom", bid.list_price.denom)
+                .add_attribute("amount", bid.list_price.amount.to_string())
+                .add_event(sale_event)
+                .add_messages(messages)
+                .add_submessages(hook_msgs))
+        }
+    }
+
+    //Counter-offers made on an earlier listing of the token no longer apply
+    remove_counter_offers(deps.storage, &ask.collection, &ask.token_id)?;
     store_ask(deps.storage, &ask)?;
+    record_activity(
+        deps.storage,
+        &env,
+        ActivityEntry {
+            activity_type: ActivityType::Listing,
+            collection: ask.collection.clone(),
+            token_id: Some(ask.token_id.clone()),
+            actor: ask.seller.clone(),
+            counterparty: None,
+            price: Some(ask.list_price.clone()),
+        }
+    )?;
+
+    let hook_msgs = prepare_hooks(deps.storage, HookType::Ask, MarketplaceHookMsg::AskHook(AskHookMsg { ask: ask.clone() }))?;
 
     Ok(Response::new()
+        .add_event(events::ask_created(&ask))
+        .add_submessages(hook_msgs)
         .add_attribute("action", "Put NFT on Sale")
         .add_attribute("token_id", rcv_msg.token_id)
         .add_attribute("seller", rcv_msg.sender))
@@ -269,6 +436,13 @@ fn execute_receive(
     rcv_msg: Cw20ReceiveMsg,
 )-> Result<Response, ContractError> {
 
+    //Sends that are not purchases, like funding the reward pool
+    if from_json::<BuyNft>(&rcv_msg.msg).is_err() {
+        if let Ok(hook_msg) = from_json::<TokenHookMsg>(&rcv_msg.msg) {
+            return execute_token_hook(deps, env, info, rcv_msg, hook_msg);
+        }
+    }
+
     let state = CONFIG.load(deps.storage)?;
     let bid_limit = state.bid_limit;
 
@@ -280,10 +454,21 @@ fn execute_receive(
     }
     let token_symbol = token_symbol.unwrap();
 
+    //Zero amount orders would fill the bid limit and their refunds would revert
+    if rcv_msg.amount.is_zero() {
+        return Err(ContractError::NotEnoughFunds {  })
+    }
+
     let msg:BuyNft = from_binary(&rcv_msg.msg)?;
     let nft_address = msg.nft_address;
     let token_id = msg.token_id;
     let token_address = info.sender.to_string();
+    let finder = msg.finder;
+    let origin_receiver = msg.origin_receiver;
+    if let Some(finder) = &finder {
+        deps.api.addr_validate(finder)?;
+        validate_finder(deps.storage, finder, &rcv_msg.sender, &nft_address, &token_id)?;
+    }
 
     //Collection Validation
     deps.api.addr_validate(&nft_address)?;
@@ -293,6 +478,10 @@ fn execute_receive(
     }
 
     let collection_info = collection_info.unwrap();
+    //cw1155 collections trade through their own orders
+    if collection_info.standard != NftStandard::Cw721 {
+        return Err(ContractError::WrongNFTContractError {  })
+    }
 
   
     //Bid or Buy with fixed_price
@@ -314,20 +503,69 @@ fn execute_receive(
                     if ask.is_expired(&env.block) {
                         return Err(ContractError::AskExpired {  })
                     }
+                    if ask.is_reserved_for_other(&bidder) {
+                        return Err(ContractError::ReservedAsk {  })
+                    }
                 },
                 None =>{
                     return Err(ContractError::NoSuchAsk {  })
                 }
             }
             
+            //A bid reaching the buy-now price settles right away through the fixed price path
+            let ask = existing_ask.clone().unwrap();
+            let bid_price = Asset { denom: token_symbol.clone(), amount: rcv_msg.amount };
+            if let Some(buy_now_price) = ask.buy_now_price_for(&bid_price) {
+                let mut messages: Vec<CosmosMsg> = Vec::new();
+                if rcv_msg.amount > buy_now_price {
+                    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr: token_address.clone(),
+                        msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                            recipient: bidder.clone(),
+                            amount: rcv_msg.amount - buy_now_price })?,
+                        funds: vec![] }));
+                }
+                let price = Asset { denom: token_symbol, amount: buy_now_price };
+                let sale_event = events::sale(&nft_address, &token_id, &ask.seller, &bidder, &price, "buy_now");
+                let hook_msgs = settle_fixed_price_sale(
+                    deps,
+                    env,
+                    info,
+                    ask,
+                    Purchase {
+                        buyer: bidder.clone(),
+                        price,
+                        token_address: Some(token_address),
+                        finder,
+                        origin_receiver,
+                    },
+                    collection_info.royalty_portion,
+                    &mut messages
+                )?;
+                return Ok(Response::new()
+                    .add_event(sale_event)
+                    .add_attribute("action", "buy Nft at buy now price with token")
+                    .add_attribute("bidder", bidder)
+                    .add_messages(messages)
+                    .add_submessages(hook_msgs))
+            }
+
             let existing_bids_token = query_bids(deps.as_ref(), nft_address.clone(), token_id.clone(), None, Some(MAX_QUERY_LIMIT))?;
             
 
             let mut messages: Vec<CosmosMsg> = Vec::new();
+
+            //the bid is validated before any other bid is evicted or refunded
+            validate_bid_rules(deps.as_ref(), &env, &ask, &collection_info, &bidder, &bid_price)?;
             
-            //bid count check
-            if existing_bids_token.bids.len() >= bid_limit as usize{
-                return Err(ContractError::BidCountExpired {  })
+            //bid count check, a full token makes room by evicting an expired or the lowest bid
+            let mut evicted_event = None;
+            if existing_bids_token.bids.len() >= bid_limit as usize && bids().may_load(deps.storage, bid_key.clone())?.is_none(){
+                let evicted = find_evictable_bid(deps.as_ref(), &env, &existing_bids_token.bids, &nft_address, &token_id, &bid_price)?;
+                bids().remove(deps.storage, (evicted.collection.clone(), evicted.token_id.clone(), evicted.bidder.clone()))?;
+                remove_counter_offer(deps.storage, &evicted.collection, &evicted.token_id, &evicted.bidder)?;
+                messages.push(refund_msg(&evicted.bidder, &evicted.list_price, &evicted.token_address)?);
+                evicted_event = Some(events::bid_removed(&evicted));
             }
             
             //Refund money if this bidder bided for this token in the past
@@ -360,30 +598,65 @@ fn execute_receive(
                 token_address: Some(token_address),
                 list_price: Asset { denom: token_symbol, amount: rcv_msg.amount },
                 expires_at: msg.expire,
-                seller: existing_ask.unwrap().seller
+                seller: existing_ask.unwrap().seller,
+                finder
             };
             if bid.is_expired(&env.block){
                 return Err(ContractError::BidExpired {  })
             }
             store_bid(deps.storage, &bid)?;
+            record_activity(
+                deps.storage,
+                &env,
+                ActivityEntry {
+                    activity_type: ActivityType::Bid,
+                    collection: bid.collection.clone(),
+                    token_id: Some(bid.token_id.clone()),
+                    actor: bid.bidder.clone(),
+                    counterparty: Some(bid.seller.clone()),
+                    price: Some(bid.list_price.clone()),
+                }
+            )?;
+            let event = events::bid_placed(&bid);
+            let hook_msgs = prepare_hooks(deps.storage, HookType::Bid, MarketplaceHookMsg::BidHook(BidHookMsg { bid }))?;
 
             if messages.len() >0 {
                 Ok(Response::new()
+                    .add_event(event)
+                    .add_events(evicted_event)
                     .add_attribute("action", "Bid for the auction")
                     .add_attribute("bidder", bidder)
                     .add_attribute("token_id", token_id)
                     .add_messages(messages)
+                    .add_submessages(hook_msgs)
                 )
             }
             else{
                  Ok(Response::new()
+                    .add_event(event)
+                    .add_events(evicted_event)
                     .add_attribute("action", "Bid for the auction")
+                    .add_submessages(hook_msgs)
                     .add_attribute("bidder", bidder)
                     .add_attribute("token_id", token_id)
                 )
             }
         }
 
+        SaleType::Offer => place_offer(
+            deps,
+            env,
+            nft_address,
+            token_id,
+            Payment {
+                sender: rcv_msg.sender,
+                amount: Asset { denom: token_symbol, amount: rcv_msg.amount },
+                token_address: Some(token_address),
+            },
+            msg.expire,
+            finder
+        ),
+
         SaleType::CollectionBid => {
            if !token_id.is_none(){
             return Err(ContractError::WrongConfig {  });
@@ -422,7 +695,8 @@ fn execute_receive(
                     denom: token_symbol,
                     amount: rcv_msg.amount 
                 },
-                expires_at: msg.expire
+                expires_at: msg.expire,
+                finder
             };
             
             if collection_bid.is_expired(&env.block){
@@ -430,17 +704,35 @@ fn execute_receive(
             }
 
             collection_bids().save(deps.storage, key, &collection_bid)?;
+            record_activity(
+                deps.storage,
+                &env,
+                ActivityEntry {
+                    activity_type: ActivityType::CollectionBid,
+                    collection: collection_bid.collection.clone(),
+                    token_id: None,
+                    actor: collection_bid.bidder.clone(),
+                    counterparty: None,
+                    price: Some(collection_bid.list_price.clone()),
+                }
+            )?;
+            let event = events::collection_bid_placed(&collection_bid);
+            let hook_msgs = prepare_hooks(deps.storage, HookType::Bid, MarketplaceHookMsg::CollectionBidHook(CollectionBidHookMsg { collection_bid }))?;
 
              if messages.len() >0 {
                 Ok(Response::new()
+                    .add_event(event)
                     .add_attribute("action", "Collection Bid for the auction")
                     .add_attribute("bidder", bidder)
                     .add_messages(messages)
+                    .add_submessages(hook_msgs)
                 )
             }
             else{
                  Ok(Response::new()
+                    .add_event(event)
                     .add_attribute("action", "Collection Bid for the auction")
+                    .add_submessages(hook_msgs)
                     .add_attribute("bidder", bidder)
                 )
             }
@@ -462,18 +754,16 @@ fn execute_receive(
                     if ask.is_expired(&env.block) {
                         return Err(ContractError::AskExpired {  })
                     }
+                    if ask.is_reserved_for_other(&bidder) {
+                        return Err(ContractError::ReservedAsk {  })
+                    }
                 },
                 None =>{
                     return Err(ContractError::NoSuchAsk {  })
                 }
             }
             
-            let existing_bids_token = query_bids(deps.as_ref(), nft_address.clone(), token_id.clone(), None, Some(MAX_QUERY_LIMIT))?;
-            
-
-            let mut messages: Vec<CosmosMsg> = Vec::new();
             let existing_ask = existing_ask.unwrap();
-            asks().remove(deps.storage, ask_key)?;
 
             //token amount validation for the fixed price sale
             if token_symbol != existing_ask.list_price.denom{
@@ -483,54 +773,31 @@ fn execute_receive(
                 return Err(ContractError::NotEnoughFunds {  })
             }
 
-            //bid information for this token_id;
-            for bid in existing_bids_token.bids{
-                match bid.token_address{
-                    Some(token_address) =>{
-                        messages.push(CosmosMsg::Wasm(WasmMsg::Execute { 
-                            contract_addr: token_address.clone(),
-                            msg: to_binary(&Cw20ExecuteMsg::Transfer { 
-                                recipient: bid.bidder.clone(),
-                                amount: bid.list_price.amount })?,
-                            funds: vec![] }));
-                    }   
-                    None =>{
-                        messages.push(CosmosMsg::Bank(BankMsg::Send {
-                            to_address: bid.bidder.clone(),
-                            amount: vec![Coin{denom: bid.list_price.denom, amount: bid.list_price.amount}] }));               
-                    }
-
-                }
-                bids().remove(deps.storage, (nft_address.clone(), token_id.clone(), bid.bidder))?;                
-            }
-
-            update_sale_history_tvl(
-                deps.storage,
+            let mut messages: Vec<CosmosMsg> = Vec::new();
+            let price = existing_ask.list_price.clone();
+            let sale_event = events::sale(&nft_address, &token_id, &existing_ask.seller, &bidder, &price, "fixed_price");
+            let hook_msgs = settle_fixed_price_sale(
+                deps,
                 env,
                 info,
-                existing_ask.seller.clone(),
-                bidder.clone(),
-                nft_address.clone(),
-                token_id.clone(),
-                existing_ask.list_price.clone()
-            )?;
-
-            distribute_money(
-                deps.as_ref(),
-                nft_address, 
-                collection_info.royalty_portion, 
-                existing_ask.seller, 
-                bidder.clone(), 
-                existing_ask.list_price, 
-                Some(token_address), 
-                token_id, 
+                existing_ask,
+                Purchase {
+                    buyer: bidder.clone(),
+                    price,
+                    token_address: Some(token_address),
+                    finder,
+                    origin_receiver,
+                },
+                collection_info.royalty_portion,
                 &mut messages
             )?;
-            
+
             Ok(Response::new()
+                .add_event(sale_event)
                 .add_attribute("action", "buy Nft as fixed price with token")
                 .add_attribute("bidder", bidder)
-                .add_messages(messages))
+                .add_messages(messages)
+                .add_submessages(hook_msgs))
         }
     }
 
@@ -544,7 +811,9 @@ fn execute_bid_with_coin(
     token_id: Option<String>,
     expire: Timestamp,
     sale_type: SaleType,   
-    list_price: Asset
+    list_price: Asset,
+    finder: Option<String>,
+    origin_receiver: Option<String>
 ) -> Result<Response, ContractError> {
 
     let state = CONFIG.load(deps.storage)?;
@@ -557,6 +826,15 @@ fn execute_bid_with_coin(
     }
 
     let collection_info = collection_info.unwrap();
+    //cw1155 collections trade through their own orders
+    if collection_info.standard != NftStandard::Cw721 {
+        return Err(ContractError::WrongNFTContractError {  })
+    }
+
+    if let Some(finder) = &finder {
+        deps.api.addr_validate(finder)?;
+        validate_finder(deps.storage, finder, info.sender.as_str(), &nft_address, &token_id)?;
+    }
 
     let is_registered_coin = COINDENOM.may_load(deps.storage, &list_price.denom)?;
     if is_registered_coin.is_none(){
@@ -571,7 +849,8 @@ fn execute_bid_with_coin(
         .map(|c| Uint128::from(c.amount))
         .unwrap_or_else(Uint128::zero);
     
-    if list_price.amount != amount{
+    //Zero amount orders would fill the bid limit and their refunds would revert
+    if amount.is_zero() || list_price.amount != amount{
         return Err(ContractError::NotEnoughFunds {  })
     }
 
@@ -595,18 +874,63 @@ fn execute_bid_with_coin(
                     if ask.is_expired(&env.block) {
                         return Err(ContractError::AskExpired {  })
                     }
+                    if ask.is_reserved_for_other(&bidder) {
+                        return Err(ContractError::ReservedAsk {  })
+                    }
                 },
                 None =>{
                     return Err(ContractError::NoSuchAsk {  })
                 }
             }
 
+            //A bid reaching the buy-now price settles right away through the fixed price path
+            let ask = existing_ask.clone().unwrap();
+            if let Some(buy_now_price) = ask.buy_now_price_for(&list_price) {
+                let mut messages: Vec<CosmosMsg> = Vec::new();
+                if list_price.amount > buy_now_price {
+                    messages.push(CosmosMsg::Bank(BankMsg::Send {
+                        to_address: bidder.clone(),
+                        amount: vec![Coin{ denom: list_price.denom.clone(), amount: list_price.amount - buy_now_price }] }));
+                }
+                let price = Asset { denom: list_price.denom, amount: buy_now_price };
+                let sale_event = events::sale(&nft_address, &token_id, &ask.seller, &bidder, &price, "buy_now");
+                let hook_msgs = settle_fixed_price_sale(
+                    deps,
+                    env,
+                    info,
+                    ask,
+                    Purchase {
+                        buyer: bidder.clone(),
+                        price,
+                        token_address: None,
+                        finder,
+                        origin_receiver,
+                    },
+                    collection_info.royalty_portion,
+                    &mut messages
+                )?;
+                return Ok(Response::new()
+                    .add_event(sale_event)
+                    .add_attribute("action", "buy Nft at buy now price with coin")
+                    .add_attribute("bidder", bidder)
+                    .add_messages(messages)
+                    .add_submessages(hook_msgs))
+            }
+
             let mut messages: Vec<CosmosMsg> = Vec::new();
 
-            //bid count check
+            //the bid is validated before any other bid is evicted or refunded
+            validate_bid_rules(deps.as_ref(), &env, &ask, &collection_info, &bidder, &list_price)?;
+
             let existing_bids_token = query_bids(deps.as_ref(), nft_address.clone(), token_id.clone(), None, Some(MAX_QUERY_LIMIT))?;
-            if existing_bids_token.bids.len() >= bid_limit as usize{
-                return Err(ContractError::BidCountExpired {  })
+            //bid count check, a full token makes room by evicting an expired or the lowest bid
+            let mut evicted_event = None;
+            if existing_bids_token.bids.len() >= bid_limit as usize && bids().may_load(deps.storage, bid_key.clone())?.is_none(){
+                let evicted = find_evictable_bid(deps.as_ref(), &env, &existing_bids_token.bids, &nft_address, &token_id, &list_price)?;
+                bids().remove(deps.storage, (evicted.collection.clone(), evicted.token_id.clone(), evicted.bidder.clone()))?;
+                remove_counter_offer(deps.storage, &evicted.collection, &evicted.token_id, &evicted.bidder)?;
+                messages.push(refund_msg(&evicted.bidder, &evicted.list_price, &evicted.token_address)?);
+                evicted_event = Some(events::bid_removed(&evicted));
             }
             
             //Refund money if this bidder bided for this token in the past
@@ -639,24 +963,45 @@ fn execute_bid_with_coin(
                 token_address: None,
                 list_price: list_price.clone(),
                 expires_at: expire,
-                seller: existing_ask.unwrap().seller
+                seller: existing_ask.unwrap().seller,
+                finder
             };
             if bid.is_expired(&env.block){
                 return Err(ContractError::BidExpired {  })
             }
             store_bid(deps.storage, &bid)?;
+            record_activity(
+                deps.storage,
+                &env,
+                ActivityEntry {
+                    activity_type: ActivityType::Bid,
+                    collection: bid.collection.clone(),
+                    token_id: Some(bid.token_id.clone()),
+                    actor: bid.bidder.clone(),
+                    counterparty: Some(bid.seller.clone()),
+                    price: Some(bid.list_price.clone()),
+                }
+            )?;
+            let event = events::bid_placed(&bid);
+            let hook_msgs = prepare_hooks(deps.storage, HookType::Bid, MarketplaceHookMsg::BidHook(BidHookMsg { bid }))?;
 
            if messages.len() >0 {
                 Ok(Response::new()
+                    .add_event(event)
+                    .add_events(evicted_event)
                     .add_attribute("action", "Bid for the auction")
                     .add_attribute("bidder", bidder)
                     .add_attribute("token_id", token_id)
                     .add_messages(messages)
+                    .add_submessages(hook_msgs)
                 )
             }
             else{
                  Ok(Response::new()
+                    .add_event(event)
+                    .add_events(evicted_event)
                     .add_attribute("action", "Bid for the auction")
+                    .add_submessages(hook_msgs)
                     .add_attribute("bidder", bidder)
                     .add_attribute("token_id", token_id)
                 )
@@ -664,6 +1009,20 @@ fn execute_bid_with_coin(
     
         }
         
+        SaleType::Offer => place_offer(
+            deps,
+            env,
+            nft_address,
+            token_id,
+            Payment {
+                sender: info.sender.to_string(),
+                amount: list_price,
+                token_address: None,
+            },
+            expire,
+            finder
+        ),
+
         SaleType::CollectionBid =>{
             let mut messages:Vec<CosmosMsg> = Vec::new();
             let bidder = info.sender.to_string();
@@ -698,7 +1057,8 @@ fn execute_bid_with_coin(
                 bidder: bidder.clone(),
                 token_address: None,
                 list_price: list_price.clone(),
-                expires_at: expire
+                expires_at: expire,
+                finder
             };
             
             if collection_bid.is_expired(&env.block){
@@ -706,24 +1066,40 @@ fn execute_bid_with_coin(
             }
 
             collection_bids().save(deps.storage, key, &collection_bid)?;
+            record_activity(
+                deps.storage,
+                &env,
+                ActivityEntry {
+                    activity_type: ActivityType::CollectionBid,
+                    collection: collection_bid.collection.clone(),
+                    token_id: None,
+                    actor: collection_bid.bidder.clone(),
+                    counterparty: None,
+                    price: Some(collection_bid.list_price.clone()),
+                }
+            )?;
+            let event = events::collection_bid_placed(&collection_bid);
+            let hook_msgs = prepare_hooks(deps.storage, HookType::Bid, MarketplaceHookMsg::CollectionBidHook(CollectionBidHookMsg { collection_bid }))?;
 
              if messages.len() >0 {
                 Ok(Response::new()
+                    .add_event(event)
                     .add_attribute("action", "Collection Bid for the auction")
                     .add_attribute("bidder", bidder)
                     .add_messages(messages)
+                    .add_submessages(hook_msgs)
                 )
             }
             else{
                  Ok(Response::new()
+                    .add_event(event)
                     .add_attribute("action", "Collection Bid for the auction")
+                    .add_submessages(hook_msgs)
                     .add_attribute("bidder", bidder)
                 )
             }
         }
         SaleType::FixedPrice =>{
-            let mut messages: Vec<CosmosMsg> = Vec::new();
-
             if token_id.is_none(){
                 return Err(ContractError::WrongConfig {  });
             }
@@ -739,74 +1115,49 @@ fn execute_bid_with_coin(
                     if ask.is_expired(&env.block) {
                         return Err(ContractError::AskExpired {  })
                     }
+                    if ask.is_reserved_for_other(&bidder) {
+                        return Err(ContractError::ReservedAsk {  })
+                    }
                 },
                 None =>{
                     return Err(ContractError::NoSuchAsk {  })
                 }
             }
 
-            let existing_bids_token = query_bids(deps.as_ref(), nft_address.clone(), token_id.clone(), None, Some(MAX_QUERY_LIMIT))?;
-  
             let existing_ask = existing_ask.unwrap();
 
-            asks().remove(deps.storage, ask_key.clone())?;
-            
             //token amount validation for the fixed price sale
-            if list_price.denom.clone() != existing_ask.list_price.denom.clone(){
+            if list_price.denom != existing_ask.list_price.denom{
                 return Err(ContractError::NotEnoughFunds {  })
             }
             if list_price.amount != existing_ask.list_price.amount{
                 return Err(ContractError::NotEnoughFunds {  })
             }
 
-            //bid information for this token_id;
-            for bid in existing_bids_token.bids{
-                match bid.token_address{
-                    Some(token_address) =>{
-                        messages.push(CosmosMsg::Wasm(WasmMsg::Execute { 
-                            contract_addr: token_address,
-                            msg: to_binary(&Cw20ExecuteMsg::Transfer { 
-                                recipient: bid.bidder.clone(),
-                                amount: bid.list_price.amount })?,
-                            funds: vec![] }));
-                    }   
-                    None =>{
-                        messages.push(CosmosMsg::Bank(BankMsg::Send {
-                            to_address: bid.bidder.clone(),
-                            amount: vec![Coin{denom: bid.list_price.denom.clone(), amount: bid.list_price.amount}] }));               
-                    }
-
-                }
-                bids().remove(deps.storage, (nft_address.clone(), token_id.clone(), bid.bidder))?;                
-            }
-
-            update_sale_history_tvl(
-                deps.storage,
-                 env, 
-                 info, 
-                 existing_ask.seller.clone(), 
-                 bidder.clone(), 
-                 nft_address.clone(), 
-                 token_id.clone(), 
-                 existing_ask.list_price.clone()
-            )?;
-            
-            distribute_money(
-                deps.as_ref(),
-                nft_address.clone(),
+            let mut messages: Vec<CosmosMsg> = Vec::new();
+            let sale_event = events::sale(&nft_address, &token_id, &existing_ask.seller, &bidder, &list_price, "fixed_price");
+            let hook_msgs = settle_fixed_price_sale(
+                deps,
+                env,
+                info,
+                existing_ask,
+                Purchase {
+                    buyer: bidder.clone(),
+                    price: list_price,
+                    token_address: None,
+                    finder,
+                    origin_receiver,
+                },
                 collection_info.royalty_portion,
-                existing_ask.seller.clone(),
-                bidder.clone(),
-                 existing_ask.list_price,
-                None,
-                token_id.clone(),
-                & mut messages
-            )?;           
-            
+                &mut messages
+            )?;
+
             Ok(Response::new()
+                .add_event(sale_event)
                 .add_attribute("action", "buy Nft as fixed price with coin")
                 .add_attribute("bidder", bidder)
-                .add_messages(messages))
+                .add_messages(messages)
+                .add_submessages(hook_msgs))
         }
     }
 
@@ -814,7 +1165,7 @@ fn execute_bid_with_coin(
 
 fn execute_withdraw(
     deps: DepsMut,
-    _env:Env,
+    env:Env,
     info: MessageInfo,
     nft_address: String,
     token_id:String
@@ -831,6 +1182,20 @@ fn execute_withdraw(
     }
 
     asks().remove(deps.storage, ask_key)?;
+    remove_counter_offers(deps.storage, &nft_address, &token_id)?;
+    record_activity(
+        deps.storage,
+        &env,
+        ActivityEntry {
+            activity_type: ActivityType::Cancellation,
+            collection: nft_address.clone(),
+            token_id: Some(token_id.clone()),
+            actor: ask.seller.clone(),
+            counterparty: None,
+            price: Some(ask.list_price.clone()),
+        }
+    )?;
+    let event = events::ask_removed(&ask);
 
     
     //bid information for this token_id;
@@ -863,6 +1228,7 @@ fn execute_withdraw(
          funds: vec![] }));
 
     Ok(Response::new()
+        .add_event(event)
         .add_attribute("action", "cancel the ask")
         .add_attribute("contract_address", nft_address)
         .add_attribute("token_id", token_id)
@@ -870,9 +1236,33 @@ fn execute_withdraw(
 }
 
 
+/// Updates any of the price, payment asset, expiry and auction settings of a live ask at once.
+/// Open bids in the new list price denom are kept, bids in any other denom are refunded
+fn execute_update_ask(
+    deps: DepsMut,
+    env:Env,
+    info: MessageInfo,
+    msg: UpdateAskMsg
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let nft_address = msg.nft_address.clone();
+    let token_id = msg.token_id.clone();
+    let (ask, messages, refund_events) = update_ask(deps, &env, info.sender.to_string(), msg)?;
+
+    Ok(Response::new()
+        .add_event(events::ask_updated(&ask))
+        .add_events(refund_events)
+        .add_attribute("action", "Update Ask")
+        .add_attribute("contract_address", nft_address)
+        .add_attribute("token_id", token_id)
+        .add_messages(messages))
+}
+
+/// Changes the list price only, with the same bid refunds as `UpdateAsk`.
+/// The buy-now price is dropped once it is below the new list price
 fn execute_update_ask_price(
     deps: DepsMut,
-    _env:Env,
+    env:Env,
     info: MessageInfo,
     nft_address: String,
     token_id:String,
@@ -880,60 +1270,321 @@ fn execute_update_ask_price(
     token_address: Option<String>
 ) -> Result<Response, ContractError> {
     nonpayable(&info)?;
-    let sender = info.sender.to_string();
-    
+    let ask = asks().load(deps.storage, ask_key(&nft_address, &token_id))?;
+    let mut clear = Vec::new();
+    if ask.buy_now_price.is_some_and(|buy_now_price| buy_now_price < list_price.amount) {
+        clear.push(AskSetting::BuyNowPrice);
+    }
+
+    let denom = list_price.denom.clone();
+    let amount = list_price.amount;
+    let (ask, messages, refund_events) = update_ask(deps, &env, info.sender.to_string(), UpdateAskMsg {
+        nft_address: nft_address.clone(),
+        token_id: token_id.clone(),
+        list_price: Some(list_price),
+        token_address,
+        expire: None,
+        buy_now_price: None,
+        reserve_price: None,
+        min_bid_increment: None,
+        clear
+    })?;
+
+    Ok(Response::new()
+        .add_event(events::ask_price_updated(&ask))
+        .add_events(refund_events)
+        .add_attribute("action", "Update Price")
+        .add_attribute("contract_address", nft_address)
+        .add_attribute("token_id", token_id)
+        .add_attribute("denom", denom)
+        .add_attribute("amount", amount.to_string())
+        .add_messages(messages))
+}
+
+/// Applies an ask update for the seller and refunds every bid on the token in another denom than the new list price.
+/// A denom change drops the amounts and increment of the old denom unless they are set again
+fn update_ask(
+    deps: DepsMut,
+    env: &Env,
+    sender: String,
+    msg: UpdateAskMsg
+) -> Result<(Ask, Vec<CosmosMsg>, Vec<Event>), ContractError> {
+    let UpdateAskMsg { nft_address, token_id, list_price, token_address, expire, buy_now_price, reserve_price, min_bid_increment, clear } = msg;
+
     let ask_key = ask_key(&nft_address, &token_id);
     let mut ask = asks().load(deps.storage, ask_key.clone())?;
-   
     if ask.seller != sender{
         return Err(ContractError::Unauthorized {  })
     }
+    if ask.is_expired(&env.block) {
+        return Err(ContractError::AskExpired {  })
+    }
 
-    let denom = list_price.denom.clone();
-    match token_address{
-      Some(token_address) => {
-        match TOKENADDRESS.may_load(deps.storage, &token_address)? {
-            Some(token_denom) =>{
-                if token_denom != denom {
-                    return Err(ContractError::WrongTokenContractError {  })
-                }
-                else{
-                    ask.list_price = list_price.clone();
-                    asks().save(deps.storage, ask_key, &ask)?;
-                }
-            }
-            None =>{
-                return Err(ContractError::WrongTokenContractError {  })
+    let price_updated = list_price.is_some();
+    match list_price {
+        Some(list_price) => {
+            validate_list_price(deps.storage, &list_price, &token_address)?;
+            if list_price.denom != ask.list_price.denom {
+                ask.buy_now_price = None;
+                ask.reserve_price = None;
+                ask.min_bid_increment = None;
             }
+            ask.list_price = list_price;
         }
-      }
-      None => {
-         match COINDENOM.may_load(deps.storage, &denom)? {
-            Some(_result) => {
-                ask.list_price = list_price.clone();
-                asks().save(deps.storage, ask_key, &ask)?;
-            },
-            None => {
-                return Err(ContractError::WrongCoinDenom {  })
+        None => {
+            if token_address.is_some() {
+                return Err(ContractError::WrongConfig {  })
             }
         }
+    }
+    if let Some(expire) = expire {
+        ask.expires_at = expire;
+        if ask.is_expired(&env.block) {
+            return Err(ContractError::AskExpired {  })
         }
     }
-    
-    Ok(Response::new()
-        .add_attribute("action", "Update Price")
-        .add_attribute("contract_address", nft_address)
-        .add_attribute("token_id", token_id)
-        .add_attribute("denom", denom)
-        .add_attribute("amount", list_price.amount.to_string())
-      )
+    if buy_now_price.is_some() {
+        ask.buy_now_price = buy_now_price;
+    }
+    if reserve_price.is_some() {
+        ask.reserve_price = reserve_price;
+    }
+    if min_bid_increment.is_some() {
+        ask.min_bid_increment = min_bid_increment;
+    }
+    //a setting is either changed or cleared
+    for setting in clear {
+        match setting {
+            AskSetting::BuyNowPrice if buy_now_price.is_none() => ask.buy_now_price = None,
+            AskSetting::ReservePrice if reserve_price.is_none() => ask.reserve_price = None,
+            AskSetting::MinBidIncrement if min_bid_increment.is_none() => ask.min_bid_increment = None,
+            _ => return Err(ContractError::WrongConfig {  })
+        }
+    }
+    if let Some(buy_now_price) = ask.buy_now_price {
+        if buy_now_price < ask.list_price.amount || buy_now_price < ask.reserve_price.unwrap_or_default() {
+            return Err(ContractError::WrongConfig {  })
+        }
+    }
+    asks().save(deps.storage, ask_key, &ask)?;
+
+    //Bids in another denom than the new list price are refunded, all of them rather than a query page
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    let mut refund_events: Vec<Event> = Vec::new();
+    let other_denom_bids = bids()
+        .prefix((nft_address.clone(), token_id.clone()))
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .map(|item| item.map(|(_, bid)| bid))
+        .filter(|item| item.as_ref().map_or(true, |bid| bid.list_price.denom != ask.list_price.denom))
+        .collect::<StdResult<Vec<_>>>()?;
+    for bid in other_denom_bids {
+        bids().remove(deps.storage, (nft_address.clone(), token_id.clone(), bid.bidder.clone()))?;
+        remove_counter_offer(deps.storage, &nft_address, &token_id, &bid.bidder)?;
+        messages.push(refund_msg(&bid.bidder, &bid.list_price, &bid.token_address)?);
+        refund_events.push(events::bid_removed(&bid));
+    }
+
+    if price_updated {
+        record_activity(
+            deps.storage,
+            env,
+            ActivityEntry {
+                activity_type: ActivityType::PriceUpdate,
+                collection: nft_address,
+                token_id: Some(token_id),
+                actor: sender,
+                counterparty: None,
+                price: Some(ask.list_price.clone()),
+            }
+        )?;
+    }
+
+    Ok((ask, messages, refund_events))
 }
 
 
-/// Removes a bid made by the bidder. Bidders can only remove their own bids
-pub fn execute_remove_bid(
+/// Funds sent along with a message, as coins or as tokens of the cw20 at `token_address`
+struct Payment {
+    sender: String,
+    amount: Asset,
+    token_address: Option<String>,
+}
+
+/// Tops up the sender's bid or collection bid with the coins sent, in the bid's denom
+fn execute_update_bid_with_coin(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
+    info: MessageInfo,
+    nft_address: String,
+    token_id: Option<String>,
+    expire: Option<Timestamp>
+) -> Result<Response, ContractError> {
+    let bidder = info.sender.to_string();
+    let denom = match &token_id {
+        Some(token_id) => bids().load(deps.storage, bid_key(&nft_address, token_id, &bidder))?.list_price.denom,
+        None => collection_bids().load(deps.storage, collection_bid_key(&nft_address, &bidder))?.list_price.denom,
+    };
+
+    //Only the bid denom can be sent, sending nothing just changes the expiry
+    if info.funds.iter().any(|c| c.denom != denom) {
+        return Err(ContractError::WrongCoinDenom {  })
+    }
+    let amount = info
+        .funds
+        .iter()
+        .map(|c| c.amount)
+        .sum();
+    let top_up = Payment { sender: bidder, amount: Asset { denom, amount }, token_address: None };
+    execute_update_bid(deps, env, info, nft_address, token_id, top_up, expire)
+}
+
+/// Raises a bid or collection bid by `top_up` and optionally moves its expiry, keeping the escrow in place.
+/// A raised bid follows the same rules as a new one, including settling at the buy-now price
+fn execute_update_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft_address: String,
+    token_id: Option<String>,
+    payment: Payment,
+    expire: Option<Timestamp>
+) -> Result<Response, ContractError> {
+    let Payment { sender: bidder, amount: top_up, token_address } = payment;
+    if top_up.amount.is_zero() && expire.is_none() {
+        return Err(ContractError::WrongConfig {  })
+    }
+
+    let token_id = match token_id {
+        Some(token_id) => token_id,
+        None => {
+            let key = collection_bid_key(&nft_address, &bidder);
+            let mut collection_bid = collection_bids().load(deps.storage, key.clone())?;
+            if collection_bid.token_address != token_address || collection_bid.list_price.denom != top_up.denom {
+                return Err(ContractError::WrongTokenContractError {  })
+            }
+            if collection_bid.is_expired(&env.block) {
+                return Err(ContractError::BidExpired {  })
+            }
+            collection_bid.list_price.amount += top_up.amount;
+            if let Some(expire) = expire {
+                collection_bid.expires_at = expire;
+            }
+            if collection_bid.is_expired(&env.block) {
+                return Err(ContractError::BidExpired {  })
+            }
+            collection_bids().save(deps.storage, key, &collection_bid)?;
+            record_activity(
+                deps.storage,
+                &env,
+                ActivityEntry {
+                    activity_type: ActivityType::BidUpdate,
+                    collection: collection_bid.collection.clone(),
+                    token_id: None,
+                    actor: bidder.clone(),
+                    counterparty: None,
+                    price: Some(collection_bid.list_price.clone()),
+                }
+            )?;
+            let event = events::collection_bid_updated(&collection_bid);
+            let hook_msgs = prepare_hooks(deps.storage, HookType::Bid, MarketplaceHookMsg::CollectionBidHook(CollectionBidHookMsg { collection_bid }))?;
+            return Ok(Response::new()
+                .add_event(event)
+                .add_submessages(hook_msgs)
+                .add_attribute("action", "Update collection bid")
+                .add_attribute("bidder", bidder))
+        }
+    };
+
+    let key = bid_key(&nft_address, &token_id, &bidder);
+    let mut bid = bids().load(deps.storage, key.clone())?;
+    if bid.token_address != token_address || bid.list_price.denom != top_up.denom {
+        return Err(ContractError::WrongTokenContractError {  })
+    }
+    if bid.is_expired(&env.block) {
+        return Err(ContractError::BidExpired {  })
+    }
+    let ask = asks().may_load(deps.storage, ask_key(&nft_address, &token_id))?
+        .ok_or(ContractError::NoSuchAsk {  })?;
+    if ask.is_expired(&env.block) {
+        return Err(ContractError::AskExpired {  })
+    }
+
+    bid.list_price.amount += top_up.amount;
+    if let Some(expire) = expire {
+        bid.expires_at = expire;
+    }
+    if bid.is_expired(&env.block) {
+        return Err(ContractError::BidExpired {  })
+    }
+    //The raised bid is checked against the other bids only
+    bids().remove(deps.storage, key)?;
+
+    //A bid reaching the buy-now price settles right away, its escrow pays for the NFT
+    if let Some(buy_now_price) = ask.buy_now_price_for(&bid.list_price) {
+        let mut messages: Vec<CosmosMsg> = Vec::new();
+        if bid.list_price.amount > buy_now_price {
+            let excess = Asset { denom: bid.list_price.denom.clone(), amount: bid.list_price.amount - buy_now_price };
+            messages.push(refund_msg(&bidder, &excess, &token_address)?);
+        }
+        let collection_info = COLLECTIONINFO.load(deps.storage, &nft_address)?;
+        let price = Asset { denom: bid.list_price.denom.clone(), amount: buy_now_price };
+        let sale_event = events::sale(&nft_address, &token_id, &ask.seller, &bidder, &price, "buy_now");
+        let hook_msgs = settle_fixed_price_sale(
+            deps,
+            env,
+            info,
+            ask,
+            Purchase {
+                buyer: bidder.clone(),
+                price,
+                token_address,
+                finder: bid.finder,
+                origin_receiver: None,
+            },
+            collection_info.royalty_portion,
+            &mut messages
+        )?;
+        return Ok(Response::new()
+            .add_event(sale_event)
+            .add_attribute("action", "buy Nft at buy now price")
+            .add_attribute("bidder", bidder)
+            .add_messages(messages)
+            .add_submessages(hook_msgs))
+    }
+
+    //Only a raise has to meet the reserve and increment, an outbid bidder can still change the expiry
+    if !top_up.amount.is_zero() {
+        let collection_info = COLLECTIONINFO.load(deps.storage, &nft_address)?;
+        validate_bid_rules(deps.as_ref(), &env, &ask, &collection_info, &bidder, &bid.list_price)?;
+    }
+    store_bid(deps.storage, &bid)?;
+    record_activity(
+        deps.storage,
+        &env,
+        ActivityEntry {
+            activity_type: ActivityType::BidUpdate,
+            collection: bid.collection.clone(),
+            token_id: Some(bid.token_id.clone()),
+            actor: bidder.clone(),
+            counterparty: Some(bid.seller.clone()),
+            price: Some(bid.list_price.clone()),
+        }
+    )?;
+    let event = events::bid_updated(&bid);
+    let hook_msgs = prepare_hooks(deps.storage, HookType::Bid, MarketplaceHookMsg::BidHook(BidHookMsg { bid }))?;
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_submessages(hook_msgs)
+        .add_attribute("action", "Update bid")
+        .add_attribute("bidder", bidder)
+        .add_attribute("token_id", token_id))
+}
+
+/// Removes a bid made by the bidder. Bidders can only remove their own bids
+pub fn execute_remove_bid(
+    deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     nft_address: String,
     token_id: String,
@@ -944,7 +1595,21 @@ pub fn execute_remove_bid(
 
     let key = bid_key(&nft_address, &token_id, &bidder);
     let bid = bids().load(deps.storage, key.clone())?;
-    bids().remove(deps.storage, key)?;
+    bids().remove(deps.storage, key.clone())?;
+    counter_offers().remove(deps.storage, key)?;
+    record_activity(
+        deps.storage,
+        &env,
+        ActivityEntry {
+            activity_type: ActivityType::BidRemoval,
+            collection: nft_address.clone(),
+            token_id: Some(token_id.clone()),
+            actor: bidder.clone(),
+            counterparty: Some(bid.seller.clone()),
+            price: Some(bid.list_price.clone()),
+        }
+    )?;
+    let event = events::bid_removed(&bid);
     
     if bid.token_address.is_none(){
         messages.push(CosmosMsg::Bank(BankMsg::Send {
@@ -962,6 +1627,7 @@ pub fn execute_remove_bid(
     }
 
     Ok(Response::new()
+        .add_event(event)
         .add_attribute("action", "remove bid")
         .add_attribute("nft_address", nft_address)
         .add_attribute("token_id", token_id)
@@ -999,11 +1665,14 @@ pub fn execute_accept_bid(
     }
 
     let existing_ask = asks().load(deps.storage, ask_key.clone())?;
-    asks().remove(deps.storage, ask_key.clone())?;
-
     if existing_ask.seller != sender{
         return Err(ContractError::Unauthorized {  })
     }
+    if existing_ask.is_expired(&env.block) {
+        return Err(ContractError::AskExpired {  })
+    }
+    asks().remove(deps.storage, ask_key)?;
+    remove_counter_offers(deps.storage, &nft_address, &token_id)?;
 
     let existing_bids_token = query_bids(deps.as_ref(), nft_address.clone(), token_id.clone(), None, Some(MAX_QUERY_LIMIT))?;
     
@@ -1032,7 +1701,7 @@ pub fn execute_accept_bid(
         }                
     }
 
-    update_sale_history_tvl(
+    let hook_msgs = update_sale_history_tvl(
         deps.storage, 
         env, 
         info, 
@@ -1040,33 +1709,41 @@ pub fn execute_accept_bid(
         bidder.clone(), 
         nft_address.clone(), 
         token_id.clone(), 
-        crr_bid.list_price.clone()
+        crr_bid.list_price.clone(),
+        None
     )?;
+    let sale_event = events::sale(&nft_address, &token_id, &existing_ask.seller, &bidder, &crr_bid.list_price, "auction");
 
     match crr_bid.token_address {
         Some(token_address) =>{
             distribute_money(
-                deps.as_ref(), 
+                deps.storage,
                 nft_address,
-                collection_info.royalty_portion, 
-                existing_ask.seller.clone(), 
-                bidder.clone(), 
-                crr_bid.list_price.clone(), 
-                Some(token_address), 
-                token_id, 
+                collection_info.royalty_portion,
+                Payout {
+                    seller: existing_ask.seller.clone(),
+                    price: crr_bid.list_price.clone(),
+                    token_address: Some(token_address),
+                    finder: crr_bid.finder.clone(),
+                },
+                bidder.clone(),
+                token_id,
                 &mut messages
             )?;        
         },
         None =>{
-             distribute_money(
-                deps.as_ref(), 
+            distribute_money(
+                deps.storage,
                 nft_address,
-                collection_info.royalty_portion, 
-                existing_ask.seller.clone(), 
-                bidder.clone(), 
-                crr_bid.list_price.clone(), 
-                None, 
-                token_id, 
+                collection_info.royalty_portion,
+                Payout {
+                    seller: existing_ask.seller.clone(),
+                    price: crr_bid.list_price.clone(),
+                    token_address: None,
+                    finder: crr_bid.finder.clone(),
+                },
+                bidder.clone(),
+                token_id,
                 &mut messages
             )?;   
         }
@@ -1074,18 +1751,740 @@ pub fn execute_accept_bid(
 
 
     Ok(Response::new()
+        .add_event(sale_event)
         .add_attribute("action", "accept bid")
         .add_attribute("seller", existing_ask.seller)
         .add_attribute("bidder", bidder)
         .add_attribute("denom", crr_bid.list_price.denom)
         .add_attribute("amount", crr_bid.list_price.amount.to_string())
-        .add_messages(messages))
+        .add_messages(messages)
+        .add_submessages(hook_msgs))
+}
+
+fn execute_make_counter_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: MakeCounterOfferMsg,
+) -> Result<Response, ContractError> {
+    let MakeCounterOfferMsg { nft_address, token_id, bidder, price, expire } = msg;
+    nonpayable(&info)?;
+    let sender = info.sender.to_string();
+
+    let ask = asks().may_load(deps.storage, ask_key(&nft_address, &token_id))?
+        .ok_or(ContractError::NoSuchAsk {  })?;
+    if ask.seller != sender {
+        return Err(ContractError::Unauthorized {  })
+    }
+    if ask.is_expired(&env.block) {
+        return Err(ContractError::AskExpired {  })
+    }
+
+    let bid = bids().load(deps.storage, bid_key(&nft_address, &token_id, &bidder))?;
+    if bid.is_expired(&env.block) {
+        return Err(ContractError::BidExpired {  })
+    }
+    //A counter-offer at or below the bid is just accepting the bid
+    if price <= bid.list_price.amount {
+        return Err(ContractError::WrongConfig {  })
+    }
+
+    let offer = CounterOffer {
+        collection: nft_address.clone(),
+        token_id: token_id.clone(),
+        seller: sender,
+        bidder: bidder.clone(),
+        price: Asset { denom: bid.list_price.denom, amount: price },
+        expires_at: expire,
+    };
+    if offer.is_expired(&env.block) {
+        return Err(ContractError::CounterOfferExpired {  })
+    }
+    counter_offers().save(deps.storage, bid_key(&nft_address, &token_id, &bidder), &offer)?;
+
+    Ok(Response::new()
+        .add_event(events::counter_offer_made(&offer))
+        .add_attribute("action", "make counter offer")
+        .add_attribute("bidder", bidder)
+        .add_attribute("token_id", token_id))
+}
+
+fn execute_remove_counter_offer(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    nft_address: String,
+    token_id: String,
+    bidder: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let key = bid_key(&nft_address, &token_id, &bidder);
+    let offer = counter_offers().load(deps.storage, key.clone())?;
+    if offer.seller != info.sender {
+        return Err(ContractError::Unauthorized {  })
+    }
+    counter_offers().remove(deps.storage, key)?;
+
+    Ok(Response::new()
+        .add_event(events::counter_offer_removed(&offer))
+        .add_attribute("action", "remove counter offer")
+        .add_attribute("bidder", bidder)
+        .add_attribute("token_id", token_id))
+}
+
+fn execute_accept_counter_offer_with_coin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft_address: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let bidder = info.sender.to_string();
+    let offer = counter_offers().load(deps.storage, bid_key(&nft_address, &token_id, &bidder))?;
+    if info.funds.iter().any(|c| c.denom != offer.price.denom) {
+        return Err(ContractError::WrongCoinDenom {  })
+    }
+    let amount = info
+        .funds
+        .iter()
+        .map(|c| c.amount)
+        .sum();
+    let top_up = Payment { sender: bidder, amount: Asset { denom: offer.price.denom, amount }, token_address: None };
+    execute_accept_counter_offer(deps, env, info, nft_address, token_id, top_up)
+}
+
+/// Sells the token at the counter-offer price. The bid escrow plus `top_up` must add up to the price exactly
+fn execute_accept_counter_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft_address: String,
+    token_id: String,
+    payment: Payment,
+) -> Result<Response, ContractError> {
+    let Payment { sender: bidder, amount: top_up, token_address } = payment;
+    let key = bid_key(&nft_address, &token_id, &bidder);
+    let offer = counter_offers().load(deps.storage, key.clone())?;
+    if offer.is_expired(&env.block) {
+        return Err(ContractError::CounterOfferExpired {  })
+    }
+
+    //The listing the offer was made on must still be open
+    let ask = asks().may_load(deps.storage, ask_key(&nft_address, &token_id))?
+        .filter(|ask| ask.seller == offer.seller)
+        .ok_or(ContractError::NoSuchAsk {  })?;
+    if ask.is_expired(&env.block) {
+        return Err(ContractError::AskExpired {  })
+    }
+
+    let bid = bids().load(deps.storage, key.clone())?;
+    if bid.is_expired(&env.block) {
+        return Err(ContractError::BidExpired {  })
+    }
+    if bid.token_address != token_address || top_up.denom != offer.price.denom {
+        return Err(ContractError::WrongTokenContractError {  })
+    }
+    if bid.list_price.amount + top_up.amount != offer.price.amount {
+        return Err(ContractError::NotEnoughFunds {  })
+    }
+
+    //The bid escrow pays for the NFT, so it is not refunded with the other bids
+    bids().remove(deps.storage, key)?;
+    remove_counter_offers(deps.storage, &nft_address, &token_id)?;
+
+    let collection_info = COLLECTIONINFO.load(deps.storage, &nft_address)?;
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    let sale_event = events::sale(&nft_address, &token_id, &ask.seller, &bidder, &offer.price, "counter_offer");
+    let hook_msgs = settle_fixed_price_sale(
+        deps,
+        env,
+        info,
+        ask,
+        Purchase {
+            buyer: bidder.clone(),
+            price: offer.price,
+            token_address,
+            finder: bid.finder,
+            origin_receiver: None,
+        },
+        collection_info.royalty_portion,
+        &mut messages
+    )?;
+
+    Ok(Response::new()
+        .add_event(sale_event)
+        .add_attribute("action", "accept counter offer")
+        .add_attribute("bidder", bidder)
+        .add_attribute("token_id", token_id)
+        .add_messages(messages)
+        .add_submessages(hook_msgs))
+}
+
+fn remove_counter_offer(store: &mut dyn Storage, collection: &str, token_id: &str, bidder: &str) -> StdResult<()> {
+    counter_offers().remove(store, bid_key(&collection.to_string(), &token_id.to_string(), &bidder.to_string()))
+}
+
+fn remove_counter_offers(store: &mut dyn Storage, collection: &str, token_id: &str) -> StdResult<()> {
+    let keys = counter_offers()
+        .idx
+        .collection_token_id
+        .prefix((collection.to_string(), token_id.to_string()))
+        .range(store, None, None, cosmwasm_std::Order::Ascending)
+        .map(|item| item.map(|(_, offer)| bid_key(&offer.collection, &offer.token_id, &offer.bidder)))
+        .collect::<StdResult<Vec<_>>>()?;
+    for key in keys {
+        counter_offers().remove(store, key)?;
+    }
+    Ok(())
+}
+
+/// Stores an offer on a token whether or not it is listed, refunding the bidder's previous offer on it
+fn place_offer(
+    deps: DepsMut,
+    env: Env,
+    nft_address: String,
+    token_id: Option<String>,
+    payment: Payment,
+    expire: Timestamp,
+    finder: Option<String>
+) -> Result<Response, ContractError> {
+    let token_id = token_id.ok_or(ContractError::WrongConfig {  })?;
+    let offer = Offer {
+        collection: nft_address,
+        token_id,
+        bidder: payment.sender,
+        price: payment.amount,
+        token_address: payment.token_address,
+        expires_at: expire,
+        finder,
+    };
+    if offer.is_expired(&env.block) {
+        return Err(ContractError::BidExpired {  })
+    }
+
+    let key = bid_key(&offer.collection, &offer.token_id, &offer.bidder);
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    if let Some(previous) = offers().may_load(deps.storage, key.clone())? {
+        messages.push(refund_msg(&previous.bidder, &previous.price, &previous.token_address)?);
+    }
+    offers().save(deps.storage, key, &offer)?;
+    record_activity(
+        deps.storage,
+        &env,
+        ActivityEntry {
+            activity_type: ActivityType::Bid,
+            collection: offer.collection.clone(),
+            token_id: Some(offer.token_id.clone()),
+            actor: offer.bidder.clone(),
+            counterparty: None,
+            price: Some(offer.price.clone()),
+        }
+    )?;
+    let event = events::offer_placed(&offer);
+    let hook_msgs = prepare_hooks(deps.storage, HookType::Bid, MarketplaceHookMsg::OfferHook(OfferHookMsg { offer: offer.clone() }))?;
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "place offer")
+        .add_attribute("bidder", offer.bidder)
+        .add_attribute("token_id", offer.token_id)
+        .add_messages(messages)
+        .add_submessages(hook_msgs))
+}
+
+fn execute_remove_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft_address: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let key = bid_key(&nft_address, &token_id, &info.sender.to_string());
+    let offer = offers().load(deps.storage, key.clone())?;
+    offers().remove(deps.storage, key)?;
+    record_activity(
+        deps.storage,
+        &env,
+        ActivityEntry {
+            activity_type: ActivityType::BidRemoval,
+            collection: offer.collection.clone(),
+            token_id: Some(offer.token_id.clone()),
+            actor: offer.bidder.clone(),
+            counterparty: None,
+            price: Some(offer.price.clone()),
+        }
+    )?;
+
+    Ok(Response::new()
+        .add_event(events::offer_removed(&offer))
+        .add_attribute("action", "remove offer")
+        .add_attribute("bidder", offer.bidder.clone())
+        .add_attribute("token_id", token_id)
+        .add_message(refund_msg(&offer.bidder, &offer.price, &offer.token_address)?))
+}
+
+fn execute_nft_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rcv_msg: Cw721ReceiveMsg,
+    collection_info: CollectionInfo,
+    hook_msg: NftHookMsg,
+) -> Result<Response, ContractError> {
+    match hook_msg {
+        NftHookMsg::AcceptOffer { bidder } => execute_accept_offer(deps, env, info, rcv_msg, collection_info, bidder),
+        NftHookMsg::AcceptCollectionBid { bidder } => execute_sell_to_collection_bid(deps, env, info, rcv_msg, bidder),
+    }
+}
+
+/// Sells the token sent to the bidder's offer. As with listings made through `SendNft`, the proceeds
+/// go to the sender, who the collection has already checked may transfer the token
+fn execute_accept_offer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rcv_msg: Cw721ReceiveMsg,
+    collection_info: CollectionInfo,
+    bidder: String,
+) -> Result<Response, ContractError> {
+    let collection = info.sender.to_string();
+    let seller = deps.api.addr_validate(&rcv_msg.sender)?.to_string();
+    let key = bid_key(&collection, &rcv_msg.token_id, &bidder);
+    let offer = offers().load(deps.storage, key.clone())?;
+    if offer.is_expired(&env.block) {
+        return Err(ContractError::BidExpired {  })
+    }
+
+    offers().remove(deps.storage, key)?;
+    let hook_msgs = update_sale_history_tvl(
+        deps.storage,
+        env,
+        info,
+        seller.clone(),
+        bidder.clone(),
+        collection.clone(),
+        offer.token_id.clone(),
+        offer.price.clone(),
+        None
+    )?;
+    let sale_event = events::sale(&collection, &offer.token_id, &seller, &bidder, &offer.price, "offer");
+
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    distribute_money(
+        deps.storage,
+        collection,
+        collection_info.royalty_portion,
+        Payout {
+            seller: seller.clone(),
+            price: offer.price.clone(),
+            token_address: offer.token_address,
+            finder: offer.finder,
+        },
+        bidder.clone(),
+        offer.token_id.clone(),
+        &mut messages
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept offer")
+        .add_attribute("token_id", offer.token_id)
+        .add_attribute("seller", seller)
+        .add_attribute("bidder", bidder)
+        .add_event(sale_event)
+        .add_messages(messages)
+        .add_submessages(hook_msgs))
+}
+
+fn execute_receive_cw1155(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rcv_msg: Cw1155ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let collection = info.sender.to_string();
+    let collection_info = load_cw1155_collection(deps.storage, &collection)?;
+    //Minted units have no previous owner, the operator receives the proceeds
+    let sender = rcv_msg.from.clone().unwrap_or_else(|| rcv_msg.operator.clone());
+    let sender = deps.api.addr_validate(&sender)?.to_string();
+
+    match from_json::<Cw1155HookMsg>(&rcv_msg.msg)? {
+        Cw1155HookMsg::Sell { unit_price, token_address, expire } => {
+            validate_list_price(deps.storage, &unit_price, &token_address)?;
+            if rcv_msg.amount.is_zero() || unit_price.amount.is_zero() {
+                return Err(ContractError::WrongConfig {  })
+            }
+            //more units join an existing listing only on the same terms
+            let key = bid_key(&collection, &rcv_msg.token_id, &sender);
+            let existing = cw1155_asks().may_load(deps.storage, key.clone())?;
+            if existing.as_ref().is_some_and(|ask| ask.unit_price != unit_price || ask.token_address != token_address || ask.expires_at != expire) {
+                return Err(ContractError::WrongConfig {  })
+            }
+            let listed = existing.map(|ask| ask.quantity).unwrap_or_default();
+            let ask = Cw1155Ask {
+                collection,
+                token_id: rcv_msg.token_id,
+                seller: sender,
+                unit_price,
+                token_address,
+                quantity: listed + rcv_msg.amount,
+                expires_at: expire,
+            };
+            if ask.is_expired(&env.block) {
+                return Err(ContractError::AskExpired {  })
+            }
+            cw1155_asks().save(deps.storage, key, &ask)?;
+            record_activity(
+                deps.storage,
+                &env,
+                ActivityEntry {
+                    activity_type: ActivityType::Listing,
+                    collection: ask.collection.clone(),
+                    token_id: Some(ask.token_id.clone()),
+                    actor: ask.seller.clone(),
+                    counterparty: None,
+                    price: Some(ask.unit_price.clone()),
+                }
+            )?;
+            let event = events::cw1155_ask_created(&ask);
+            let hook_msgs = prepare_hooks(deps.storage, HookType::Ask, MarketplaceHookMsg::Cw1155AskHook(Cw1155AskHookMsg { ask: ask.clone() }))?;
+
+            Ok(Response::new()
+                .add_event(event)
+                .add_attribute("action", "Put cw1155 on Sale")
+                .add_attribute("token_id", ask.token_id)
+                .add_attribute("seller", ask.seller)
+                .add_attribute("quantity", ask.quantity.to_string())
+                .add_submessages(hook_msgs))
+        }
+        Cw1155HookMsg::AcceptBid { bidder } => {
+            let key = bid_key(&collection, &rcv_msg.token_id, &bidder);
+            let mut bid = cw1155_bids().load(deps.storage, key.clone())?;
+            if bid.is_expired(&env.block) {
+                return Err(ContractError::BidExpired {  })
+            }
+            if rcv_msg.amount.is_zero() || rcv_msg.amount > bid.quantity {
+                return Err(ContractError::WrongConfig {  })
+            }
+            bid.quantity -= rcv_msg.amount;
+            if bid.quantity.is_zero() {
+                cw1155_bids().remove(deps.storage, key)?;
+            } else {
+                cw1155_bids().save(deps.storage, key, &bid)?;
+            }
+
+            let price = Asset { denom: bid.unit_price.denom.clone(), amount: bid.unit_price.amount * rcv_msg.amount };
+            let mut messages: Vec<CosmosMsg> = Vec::new();
+            let hook_msgs = settle_cw1155_sale(
+                deps,
+                env,
+                info,
+                &collection_info,
+                rcv_msg.token_id.clone(),
+                Cw1155Fill {
+                    seller: sender.clone(),
+                    buyer: bidder.clone(),
+                    quantity: rcv_msg.amount,
+                    unit_price: bid.unit_price,
+                    token_address: bid.token_address,
+                },
+                &mut messages
+            )?;
+
+            Ok(Response::new()
+                .add_event(events::sale(&collection, &rcv_msg.token_id, &sender, &bidder, &price, "cw1155")
+                    .add_attribute("quantity", rcv_msg.amount.to_string()))
+                .add_attribute("action", "accept cw1155 bid")
+                .add_attribute("token_id", rcv_msg.token_id)
+                .add_attribute("seller", sender)
+                .add_attribute("bidder", bidder)
+                .add_messages(messages)
+                .add_submessages(hook_msgs))
+        }
+    }
+}
+
+fn execute_buy_cw1155_with_coin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: BuyCw1155Msg,
+) -> Result<Response, ContractError> {
+    let ask = cw1155_asks().load(deps.storage, bid_key(&msg.nft_address, &msg.token_id, &msg.seller))?;
+    if info.funds.iter().any(|c| c.denom != ask.unit_price.denom) {
+        return Err(ContractError::WrongCoinDenom {  })
+    }
+    let amount = info
+        .funds
+        .iter()
+        .map(|c| c.amount)
+        .sum();
+    let payment = Payment { sender: info.sender.to_string(), amount: Asset { denom: ask.unit_price.denom, amount }, token_address: None };
+    execute_buy_cw1155(deps, env, info, msg, payment)
+}
+
+/// Fills `quantity` units of a cw1155 listing. `payment` must be exactly the unit price times the quantity
+fn execute_buy_cw1155(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: BuyCw1155Msg,
+    payment: Payment,
+) -> Result<Response, ContractError> {
+    let BuyCw1155Msg { nft_address, token_id, seller, quantity } = msg;
+    let Payment { sender: buyer, amount: payment, token_address } = payment;
+    let collection_info = load_cw1155_collection(deps.storage, &nft_address)?;
+    let key = bid_key(&nft_address, &token_id, &seller);
+    let mut ask = cw1155_asks().load(deps.storage, key.clone())?;
+    if ask.is_expired(&env.block) {
+        return Err(ContractError::AskExpired {  })
+    }
+    if quantity.is_zero() || quantity > ask.quantity {
+        return Err(ContractError::WrongConfig {  })
+    }
+    if ask.token_address != token_address || ask.unit_price.denom != payment.denom {
+        return Err(ContractError::WrongCoinDenom {  })
+    }
+    if ask.unit_price.amount * quantity != payment.amount {
+        return Err(ContractError::NotEnoughFunds {  })
+    }
+
+    ask.quantity -= quantity;
+    if ask.quantity.is_zero() {
+        cw1155_asks().remove(deps.storage, key)?;
+    } else {
+        cw1155_asks().save(deps.storage, key, &ask)?;
+    }
+
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    let hook_msgs = settle_cw1155_sale(
+        deps,
+        env,
+        info,
+        &collection_info,
+        token_id.clone(),
+        Cw1155Fill {
+            seller: seller.clone(),
+            buyer: buyer.clone(),
+            quantity,
+            unit_price: ask.unit_price,
+            token_address,
+        },
+        &mut messages
+    )?;
+
+    Ok(Response::new()
+        .add_event(events::sale(&nft_address, &token_id, &seller, &buyer, &payment, "cw1155")
+            .add_attribute("quantity", quantity.to_string()))
+        .add_attribute("action", "buy cw1155")
+        .add_attribute("token_id", token_id)
+        .add_attribute("buyer", buyer)
+        .add_attribute("quantity", quantity.to_string())
+        .add_messages(messages)
+        .add_submessages(hook_msgs))
+}
+
+fn execute_set_cw1155_bid_with_coin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: SetCw1155BidMsg,
+) -> Result<Response, ContractError> {
+    let amount = info
+        .funds
+        .iter()
+        .find(|c| c.denom == msg.unit_price.denom)
+        .map(|c| c.amount)
+        .unwrap_or_else(Uint128::zero);
+    if info.funds.len() > 1 {
+        return Err(ContractError::WrongCoinDenom {  })
+    }
+    let bid = Cw1155Bid {
+        collection: msg.nft_address,
+        token_id: msg.token_id,
+        bidder: info.sender.to_string(),
+        unit_price: msg.unit_price,
+        token_address: None,
+        quantity: msg.quantity,
+        expires_at: msg.expire,
+    };
+    execute_set_cw1155_bid(deps, env, bid, amount)
+}
+
+/// Stores a cw1155 bid escrowing `deposit`, which must be the unit price times the quantity. Refunds the bidder's previous bid on the token
+fn execute_set_cw1155_bid(
+    deps: DepsMut,
+    env: Env,
+    bid: Cw1155Bid,
+    deposit: Uint128,
+) -> Result<Response, ContractError> {
+    load_cw1155_collection(deps.storage, &bid.collection)?;
+    validate_list_price(deps.storage, &bid.unit_price, &bid.token_address)?;
+    if bid.quantity.is_zero() || bid.unit_price.amount.is_zero() {
+        return Err(ContractError::WrongConfig {  })
+    }
+    if bid.unit_price.amount * bid.quantity != deposit {
+        return Err(ContractError::NotEnoughFunds {  })
+    }
+    if bid.is_expired(&env.block) {
+        return Err(ContractError::BidExpired {  })
+    }
+
+    let key = bid_key(&bid.collection, &bid.token_id, &bid.bidder);
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    if let Some(previous) = cw1155_bids().may_load(deps.storage, key.clone())? {
+        messages.push(refund_msg(&previous.bidder, &cw1155_bid_escrow(&previous), &previous.token_address)?);
+    }
+    cw1155_bids().save(deps.storage, key, &bid)?;
+    record_activity(
+        deps.storage,
+        &env,
+        ActivityEntry {
+            activity_type: ActivityType::Bid,
+            collection: bid.collection.clone(),
+            token_id: Some(bid.token_id.clone()),
+            actor: bid.bidder.clone(),
+            counterparty: None,
+            price: Some(bid.unit_price.clone()),
+        }
+    )?;
+    let event = events::cw1155_bid_placed(&bid);
+    let hook_msgs = prepare_hooks(deps.storage, HookType::Bid, MarketplaceHookMsg::Cw1155BidHook(Cw1155BidHookMsg { bid: bid.clone() }))?;
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "Bid for cw1155")
+        .add_attribute("bidder", bid.bidder)
+        .add_attribute("token_id", bid.token_id)
+        .add_messages(messages)
+        .add_submessages(hook_msgs))
+}
+
+fn execute_remove_cw1155_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft_address: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let key = bid_key(&nft_address, &token_id, &info.sender.to_string());
+    let bid = cw1155_bids().load(deps.storage, key.clone())?;
+    cw1155_bids().remove(deps.storage, key)?;
+    record_activity(
+        deps.storage,
+        &env,
+        ActivityEntry {
+            activity_type: ActivityType::BidRemoval,
+            collection: bid.collection.clone(),
+            token_id: Some(bid.token_id.clone()),
+            actor: bid.bidder.clone(),
+            counterparty: None,
+            price: Some(bid.unit_price.clone()),
+        }
+    )?;
+
+    Ok(Response::new()
+        .add_event(events::cw1155_bid_removed(&bid))
+        .add_attribute("action", "remove cw1155 bid")
+        .add_attribute("bidder", bid.bidder.clone())
+        .add_attribute("token_id", token_id)
+        .add_message(refund_msg(&bid.bidder, &cw1155_bid_escrow(&bid), &bid.token_address)?))
+}
+
+fn execute_withdraw_cw1155(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft_address: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let key = bid_key(&nft_address, &token_id, &info.sender.to_string());
+    let ask = cw1155_asks().load(deps.storage, key.clone())?;
+    cw1155_asks().remove(deps.storage, key)?;
+    record_activity(
+        deps.storage,
+        &env,
+        ActivityEntry {
+            activity_type: ActivityType::Cancellation,
+            collection: ask.collection.clone(),
+            token_id: Some(ask.token_id.clone()),
+            actor: ask.seller.clone(),
+            counterparty: None,
+            price: None,
+        }
+    )?;
+
+    Ok(Response::new()
+        .add_event(events::cw1155_ask_removed(&ask))
+        .add_attribute("action", "withdraw cw1155")
+        .add_attribute("seller", ask.seller.clone())
+        .add_attribute("token_id", token_id)
+        .add_message(cw1155_send_msg(&ask.collection, &ask.seller, &ask.token_id, ask.quantity, &env)?))
+}
+
+/// Units of a cw1155 token changing hands at `unit_price`
+struct Cw1155Fill {
+    seller: String,
+    buyer: String,
+    quantity: Uint128,
+    unit_price: Asset,
+    token_address: Option<String>,
+}
+
+/// Records the sale at the unit price, pays royalties and the seller out of the total, and sends the units to the buyer
+fn settle_cw1155_sale(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection_info: &CollectionInfo,
+    token_id: String,
+    fill: Cw1155Fill,
+    messages: &mut Vec<CosmosMsg>
+) -> Result<Vec<SubMsg>, ContractError> {
+    let Cw1155Fill { seller, buyer, quantity, unit_price, token_address } = fill;
+    let transfer = cw1155_send_msg(&collection_info.nft_address, &buyer, &token_id, quantity, &env)?;
+    let price = Asset { denom: unit_price.denom.clone(), amount: unit_price.amount * quantity };
+    let hook_msgs = update_sale_history_tvl(
+        deps.storage,
+        env,
+        info,
+        seller.clone(),
+        buyer,
+        collection_info.nft_address.clone(),
+        token_id,
+        unit_price,
+        Some(quantity)
+    )?;
+
+    pay_out(
+        deps.storage,
+        &collection_info.nft_address,
+        collection_info.royalty_portion,
+        Payout {
+            seller,
+            price,
+            token_address,
+            finder: None,
+        },
+        messages
+    )?;
+    messages.push(transfer);
+    Ok(hook_msgs)
+}
+
+fn load_cw1155_collection(store: &dyn Storage, nft_address: &str) -> Result<CollectionInfo, ContractError> {
+    match COLLECTIONINFO.may_load(store, nft_address)? {
+        Some(collection_info) if collection_info.standard == NftStandard::Cw1155 => Ok(collection_info),
+        _ => Err(ContractError::WrongNFTContractError {  })
+    }
 }
 
 /// Remove an existing collection bid (limit order)
 pub fn execute_remove_collection_bid(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     nft_address: String,
 ) -> Result<Response, ContractError> {
@@ -1097,6 +2496,19 @@ pub fn execute_remove_collection_bid(
 
     let collection_bid = collection_bids().load(deps.storage, key.clone())?;
     collection_bids().remove(deps.storage, key)?;
+    record_activity(
+        deps.storage,
+        &env,
+        ActivityEntry {
+            activity_type: ActivityType::BidRemoval,
+            collection: nft_address.clone(),
+            token_id: None,
+            actor: bidder.clone(),
+            counterparty: None,
+            price: Some(collection_bid.list_price.clone()),
+        }
+    )?;
+    let event = events::collection_bid_removed(&collection_bid);
     
     if collection_bid.token_address.is_none(){
         messages.push(CosmosMsg::Bank(BankMsg::Send {
@@ -1115,11 +2527,13 @@ pub fn execute_remove_collection_bid(
    
     Ok(Response::new()
         .add_messages(messages)
+        .add_event(event)
         .add_attribute("action", "remove collection bidder")
         .add_attribute("collection", nft_address)
         .add_attribute("bidder", bidder))
 }
-/// Owner/seller of an item in a collection can accept a collection bid which transfers funds as well as a token
+/// Seller of a listed item in a collection can accept a collection bid which transfers funds as well as the escrowed token.
+/// Owners of unlisted items sell into a collection bid by sending the NFT with `NftHookMsg::AcceptCollectionBid`
 pub fn execute_accept_collection_bid(
     deps: DepsMut,
     env: Env,
@@ -1147,132 +2561,82 @@ pub fn execute_accept_collection_bid(
     if bid.is_expired(&env.block) {
         return Err(ContractError::BidExpired {});
     }
-    collection_bids().remove(deps.storage, bid_key)?;
-
-    let existing_ask = asks().may_load(deps.storage, ask_key.clone())?;
-    
-    match existing_ask.clone(){
-        Some(existing_ask) =>{
-            asks().remove(deps.storage, ask_key)?;
-            if existing_ask.seller != sender{
-                return Err(ContractError::Unauthorized {  });
-            }
-            if existing_ask.is_expired(&env.block){
-              return Err(ContractError::AskExpired {  })
-            }
-             //bid information for this token_id;
-            let existing_bids_token = query_bids(deps.as_ref(), nft_address.clone(), token_id.clone(), None, Some(MAX_QUERY_LIMIT))?;
-            //remove bids for this token_id
-            for each_bid in existing_bids_token.bids{
-                match each_bid.token_address{
-                    Some(token_address) =>{
-                        messages.push(CosmosMsg::Wasm(WasmMsg::Execute { 
-                            contract_addr: token_address.clone(),
-                            msg: to_binary(&Cw20ExecuteMsg::Transfer { 
-                                recipient: each_bid.bidder.clone(),
-                                amount: each_bid.list_price.amount })?,
-                            funds: vec![] }));
-                    }   
-                    None =>{
-                        messages.push(CosmosMsg::Bank(BankMsg::Send {
-                            to_address: each_bid.bidder.clone(),
-                            amount: vec![Coin{denom: each_bid.list_price.denom, amount: each_bid.list_price.amount}] }));               
-                    }
-                }
-                bids().remove(deps.storage, (nft_address.clone(), token_id.clone(), each_bid.bidder))?;                
-            }
-            
-            update_sale_history_tvl(
-                deps.storage, 
-                env, 
-                info, 
-                existing_ask.seller.clone(), 
-                bidder.clone(), 
-                nft_address.clone(), 
-                token_id.clone(), 
-                bid.list_price.clone()
-            )?;
-
-            match bid.token_address {
-                Some(token_address) =>{
-                    distribute_money(
-                        deps.as_ref(), 
-                        nft_address,
-                        collection_info.royalty_portion, 
-                        existing_ask.seller.clone(), 
-                        bidder.clone(), 
-                        bid.list_price.clone(), 
-                        Some(token_address), 
-                        token_id, 
-                        &mut messages
-                    )?;        
-                },
-                None =>{
-                    distribute_money(
-                        deps.as_ref(), 
-                        nft_address,
-                        collection_info.royalty_portion, 
-                        existing_ask.seller.clone(), 
-                        bidder.clone(), 
-                        bid.list_price.clone(), 
-                        None, 
-                        token_id, 
-                        &mut messages
-                    )?;   
-                }
-            }
 
+    //The token is only transferred out of escrow, so it must be listed
+    let existing_ask = asks().may_load(deps.storage, ask_key)?.ok_or(ContractError::NoSuchAsk {  })?;
+    if existing_ask.seller != sender{
+        return Err(ContractError::Unauthorized {  });
+    }
+    if existing_ask.is_expired(&env.block){
+        return Err(ContractError::AskExpired {  })
+    }
 
+    collection_bids().remove(deps.storage, bid_key)?;
+    let sale_event = events::sale(&nft_address, &token_id, &sender, &bidder, &bid.list_price, "collection_bid");
+
+    let hook_msgs = settle_fixed_price_sale(
+        deps,
+        env,
+        info,
+        existing_ask,
+        Purchase {
+            buyer: bidder.clone(),
+            price: bid.list_price.clone(),
+            token_address: bid.token_address,
+            finder: bid.finder,
+            origin_receiver: None,
         },
-        None =>{
-            update_sale_history_tvl(
-                deps.storage, 
-                env, 
-                info, 
-                sender.clone(), 
-                bidder.clone(), 
-                nft_address.clone(), 
-                token_id.clone(), 
-                bid.list_price.clone()
-            )?;
+        collection_info.royalty_portion,
+        &mut messages
+    )?;
 
-            match bid.token_address {
-                Some(token_address) =>{
-                    distribute_money(
-                        deps.as_ref(), 
-                        nft_address,
-                        collection_info.royalty_portion, 
-                        sender.clone(), 
-                        bidder.clone(), 
-                        bid.list_price.clone(), 
-                        Some(token_address), 
-                        token_id, 
-                        &mut messages
-                    )?;        
-                },
-                None =>{
-                    distribute_money(
-                        deps.as_ref(), 
-                        nft_address,
-                        collection_info.royalty_portion, 
-                        sender.clone(), 
-                        bidder.clone(), 
-                        bid.list_price.clone(), 
-                        None, 
-                        token_id, 
-                        &mut messages
-                    )?;   
-                }
-            }
-        }
+    Ok(Response::new()
+        .add_event(sale_event)
+        .add_attribute("action", "accept collection bid")
+        .add_attribute("bidder", bidder)
+        .add_attribute("denom", bid.list_price.denom)
+        .add_attribute("amount", bid.list_price.amount.to_string())
+        .add_messages(messages)
+        .add_submessages(hook_msgs))
+}
+
+/// Sells the token sent by its owner into a collection bid
+fn execute_sell_to_collection_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rcv_msg: Cw721ReceiveMsg,
+    bidder: String,
+) -> Result<Response, ContractError> {
+    let collection = info.sender.to_string();
+    let seller = deps.api.addr_validate(&rcv_msg.sender)?.to_string();
+    let bid = collection_bids().load(deps.storage, collection_bid_key(&collection, &bidder))?;
+    if bid.is_expired(&env.block) {
+        return Err(ContractError::BidExpired {});
     }
 
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    let sale_event = events::sale(&collection, &rcv_msg.token_id, &seller, &bidder, &bid.list_price, "collection_bid");
+    let hook_msgs = settle_collection_bid(
+        deps,
+        env,
+        info,
+        bid.clone(),
+        seller.clone(),
+        rcv_msg.token_id.clone(),
+        &mut messages
+    )?;
+
     Ok(Response::new()
+        .add_event(sale_event)
         .add_attribute("action", "accept collection bid")
+        .add_attribute("token_id", rcv_msg.token_id)
+        .add_attribute("seller", seller)
         .add_attribute("bidder", bidder)
         .add_attribute("denom", bid.list_price.denom)
         .add_attribute("amount", bid.list_price.amount.to_string())
-        .add_messages(messages))
+        .add_messages(messages)
+        .add_submessages(hook_msgs))
 }
 
 
@@ -1312,9 +2676,17 @@ fn execute_add_collection(
     MEMBERS.save(deps.storage,&nft_address, &members)?;
     COLLECTIONINFO.save(deps.storage,&nft_address,&CollectionInfo{
         nft_address:nft_address.clone(),
-        royalty_portion:royalty_potion
+        royalty_portion:royalty_potion,
+        finder_fee: Decimal::zero(),
+        min_bid_increment: Decimal::zero(),
+        min_bid_portion: Decimal::zero(),
+        standard: NftStandard::Cw721,
+        ics721: None
     })?;
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_event(Event::new("collection-added")
+            .add_attribute("collection", nft_address)
+            .add_attribute("royalty_portion", royalty_potion.to_string())))
 }
 
 
@@ -1355,10 +2727,161 @@ fn execute_update_collection(
     COLLECTIONINFO.save(deps.storage,&nft_address,&CollectionInfo{
         nft_address:nft_address.clone(),
         royalty_portion:royalty_potion,
+        ..collection_info.unwrap()
     })?;
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_event(Event::new("collection-updated")
+            .add_attribute("collection", nft_address)
+            .add_attribute("royalty_portion", royalty_potion.to_string())))
+}
+
+
+fn execute_set_finder_fee(
+    deps: DepsMut,
+    _env:Env,
+    info: MessageInfo,
+    nft_address: String,
+    finder_fee: Decimal,
+) -> Result<Response, ContractError> {
+    let state = CONFIG.load(deps.storage)?;
+    if info.sender != state.owner{
+        return Err(ContractError::Unauthorized {});
+    }
+    if finder_fee > Decimal::one(){
+        return Err(ContractError::WrongPortionError {  })
+    }
+
+    let mut collection_info = COLLECTIONINFO.may_load(deps.storage, &nft_address)?
+        .ok_or(ContractError::WrongCollection {  })?;
+    collection_info.finder_fee = finder_fee;
+    COLLECTIONINFO.save(deps.storage, &nft_address, &collection_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set finder fee")
+        .add_event(Event::new("finder-fee-set")
+            .add_attribute("collection", nft_address)
+            .add_attribute("finder_fee", finder_fee.to_string())))
+}
+
+fn execute_set_min_bid_increment(
+    deps: DepsMut,
+    _env:Env,
+    info: MessageInfo,
+    nft_address: String,
+    min_bid_increment: Decimal,
+) -> Result<Response, ContractError> {
+    let state = CONFIG.load(deps.storage)?;
+    if info.sender != state.owner{
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut collection_info = COLLECTIONINFO.may_load(deps.storage, &nft_address)?
+        .ok_or(ContractError::WrongCollection {  })?;
+    collection_info.min_bid_increment = min_bid_increment;
+    COLLECTIONINFO.save(deps.storage, &nft_address, &collection_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set min bid increment")
+        .add_event(Event::new("min-bid-increment-set")
+            .add_attribute("collection", nft_address)
+            .add_attribute("min_bid_increment", min_bid_increment.to_string())))
+}
+
+fn execute_set_min_bid_portion(
+    deps: DepsMut,
+    _env:Env,
+    info: MessageInfo,
+    nft_address: String,
+    min_bid_portion: Decimal,
+) -> Result<Response, ContractError> {
+    let state = CONFIG.load(deps.storage)?;
+    if info.sender != state.owner{
+        return Err(ContractError::Unauthorized {});
+    }
+    if min_bid_portion > Decimal::one(){
+        return Err(ContractError::WrongPortionError {  })
+    }
+
+    let mut collection_info = COLLECTIONINFO.may_load(deps.storage, &nft_address)?
+        .ok_or(ContractError::WrongCollection {  })?;
+    collection_info.min_bid_portion = min_bid_portion;
+    COLLECTIONINFO.save(deps.storage, &nft_address, &collection_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set min bid portion")
+        .add_event(Event::new("min-bid-portion-set")
+            .add_attribute("collection", nft_address)
+            .add_attribute("min_bid_portion", min_bid_portion.to_string())))
+}
+
+fn execute_set_nft_standard(
+    deps: DepsMut,
+    _env:Env,
+    info: MessageInfo,
+    nft_address: String,
+    standard: NftStandard,
+) -> Result<Response, ContractError> {
+    let state = CONFIG.load(deps.storage)?;
+    if info.sender != state.owner{
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut collection_info = COLLECTIONINFO.may_load(deps.storage, &nft_address)?
+        .ok_or(ContractError::WrongCollection {  })?;
+    collection_info.standard = standard;
+    COLLECTIONINFO.save(deps.storage, &nft_address, &collection_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set nft standard")
+        .add_event(Event::new("nft-standard-set")
+            .add_attribute("collection", nft_address)
+            .add_attribute("standard", format!("{:?}", standard).to_lowercase())))
 }
 
+fn execute_set_ics721_bridge(
+    deps: DepsMut,
+    _env:Env,
+    info: MessageInfo,
+    nft_address: String,
+    bridge: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = CONFIG.load(deps.storage)?;
+    if info.sender != state.owner{
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut collection_info = COLLECTIONINFO.may_load(deps.storage, &nft_address)?
+        .ok_or(ContractError::WrongCollection {  })?;
+    let event = Event::new("ics721-origin-set").add_attribute("collection", nft_address.clone());
+    let event = match bridge {
+        Some(bridge) => {
+            deps.api.addr_validate(&bridge)?;
+            //The bridge knows the class id of every voucher collection it instantiated
+            let class_id: Option<String> = deps.querier.query_wasm_smart(
+                bridge.clone(),
+                &Ics721QueryMsg::ClassId { contract: nft_address.clone() }
+            )?;
+            let origin = class_id
+                .and_then(|class_id| Ics721Origin::parse(bridge, class_id))
+                .ok_or(ContractError::WrongCollection {  })?;
+            let event = event
+                .add_attribute("bridge", origin.bridge.clone())
+                .add_attribute("class_id", origin.class_id.clone())
+                .add_attribute("channel_id", origin.channel_id.clone());
+            collection_info.ics721 = Some(origin);
+            event
+        }
+        None => {
+            collection_info.ics721 = None;
+            event
+        }
+    };
+    COLLECTIONINFO.save(deps.storage, &nft_address, &collection_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set ics721 bridge")
+        .add_event(event))
+}
 
 fn execute_token_address(
     deps: DepsMut,
@@ -1376,7 +2899,10 @@ fn execute_token_address(
     
     TOKENADDRESS.save(deps.storage,&address,&symbol)?;
 
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_event(Event::new("token-added")
+            .add_attribute("token_address", address)
+            .add_attribute("denom", symbol)))
 }
 
 
@@ -1394,7 +2920,8 @@ fn execute_add_coin(
     
     COINDENOM.save(deps.storage, &symbol, &true)?;
     
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_event(Event::new("coin-added").add_attribute("denom", symbol)))
 }
 
 fn execute_fix_nft(
@@ -1411,6 +2938,10 @@ fn execute_fix_nft(
     }
 
     Ok(Response::new()
+        .add_event(Event::new("nft-recovered")
+            .add_attribute("collection", address.clone())
+            .add_attribute("token_id", token_id.clone())
+            .add_attribute("recipient", info.sender.to_string()))
         .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: address,
                 funds: vec![],
@@ -1452,43 +2983,516 @@ fn execute_migrate(
     }
 
     Ok(Response::new()
+        .add_event(Event::new("nfts-migrated")
+            .add_attribute("collection", address)
+            .add_attribute("recipient", dest)
+            .add_attribute("count", messages.len().to_string()))
         .add_messages(messages))
 }
 
 
-fn execute_change_owner(
+fn execute_change_owner(
+    deps: DepsMut,
+    _env:Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG.load(deps.storage)?;
+
+    if state.admin != info.sender.to_string() {
+        return Err(ContractError::Unauthorized {});
+    }
+    deps.api.addr_validate(&address)?;
+    state.owner = address;
+    CONFIG.save(deps.storage,&state)?;
+    Ok(Response::new()
+        .add_event(Event::new("owner-changed").add_attribute("owner", state.owner)))
+}
+
+
+fn execute_change_admin(
+    deps: DepsMut,
+    _env:Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let mut state = CONFIG.load(deps.storage)?;
+
+    if state.admin != info.sender.to_string() {
+        return Err(ContractError::Unauthorized {});
+    }
+    deps.api.addr_validate(&address)?;
+    state.admin = address;
+    CONFIG.save(deps.storage,&state)?;
+    Ok(Response::new()
+        .add_event(Event::new("admin-changed").add_attribute("admin", state.admin)))
+}
+
+fn execute_add_hook(
+    deps: DepsMut,
+    _env:Env,
+    info: MessageInfo,
+    hook_type: HookType,
+    address: String,
+) -> Result<Response, ContractError> {
+    let state = CONFIG.load(deps.storage)?;
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    deps.api.addr_validate(&address)?;
+
+    let mut hooks = hook_type.hooks().may_load(deps.storage)?.unwrap_or_default();
+    if hooks.contains(&address) {
+        return Err(ContractError::HookAlreadyRegistered {});
+    }
+    hooks.push(address.clone());
+    hook_type.hooks().save(deps.storage, &hooks)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add hook")
+        .add_attribute("hook", address.clone())
+        .add_event(Event::new("hook-added")
+            .add_attribute("hook_type", format!("{:?}", hook_type).to_lowercase())
+            .add_attribute("hook", address)))
+}
+
+fn execute_remove_hook(
+    deps: DepsMut,
+    _env:Env,
+    info: MessageInfo,
+    hook_type: HookType,
+    address: String,
+) -> Result<Response, ContractError> {
+    let state = CONFIG.load(deps.storage)?;
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut hooks = hook_type.hooks().may_load(deps.storage)?.unwrap_or_default();
+    if !hooks.contains(&address) {
+        return Err(ContractError::HookNotRegistered {});
+    }
+    hooks.retain(|hook| *hook != address);
+    hook_type.hooks().save(deps.storage, &hooks)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "remove hook")
+        .add_attribute("hook", address.clone())
+        .add_event(Event::new("hook-removed")
+            .add_attribute("hook_type", format!("{:?}", hook_type).to_lowercase())
+            .add_attribute("hook", address)))
+}
+
+fn execute_token_hook(
+    deps: DepsMut,
+    env:Env,
+    info: MessageInfo,
+    rcv_msg: Cw20ReceiveMsg,
+    hook_msg: TokenHookMsg,
+) -> Result<Response, ContractError> {
+    match hook_msg {
+        TokenHookMsg::FundRewards {} => {
+            let config = REWARD_CONFIG.may_load(deps.storage)?.ok_or(ContractError::NoRewardProgram {})?;
+            if info.sender != config.token_address {
+                return Err(ContractError::WrongTokenContractError {});
+            }
+            let pool = REWARD_POOL.may_load(deps.storage)?.unwrap_or_default() + rcv_msg.amount;
+            REWARD_POOL.save(deps.storage, &pool)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "fund rewards")
+                .add_event(Event::new("rewards-funded")
+                    .add_attribute("funder", rcv_msg.sender)
+                    .add_attribute("amount", rcv_msg.amount.to_string())
+                    .add_attribute("pool", pool.to_string())))
+        }
+        TokenHookMsg::UpdateBid { nft_address, token_id, expire } => {
+            let token_symbol = TOKENADDRESS.may_load(deps.storage, info.sender.as_ref())?
+                .ok_or(ContractError::WrongTokenContractError {  })?;
+            let top_up = Payment {
+                sender: rcv_msg.sender,
+                amount: Asset { denom: token_symbol, amount: rcv_msg.amount },
+                token_address: Some(info.sender.to_string()),
+            };
+            execute_update_bid(deps, env, info, nft_address, token_id, top_up, expire)
+        }
+        TokenHookMsg::AcceptCounterOffer { nft_address, token_id } => {
+            let token_symbol = TOKENADDRESS.may_load(deps.storage, info.sender.as_ref())?
+                .ok_or(ContractError::WrongTokenContractError {  })?;
+            let top_up = Payment {
+                sender: rcv_msg.sender,
+                amount: Asset { denom: token_symbol, amount: rcv_msg.amount },
+                token_address: Some(info.sender.to_string()),
+            };
+            execute_accept_counter_offer(deps, env, info, nft_address, token_id, top_up)
+        }
+        TokenHookMsg::BuyCw1155(msg) => {
+            let token_symbol = TOKENADDRESS.may_load(deps.storage, info.sender.as_ref())?
+                .ok_or(ContractError::WrongTokenContractError {  })?;
+            let payment = Payment {
+                sender: rcv_msg.sender,
+                amount: Asset { denom: token_symbol, amount: rcv_msg.amount },
+                token_address: Some(info.sender.to_string()),
+            };
+            execute_buy_cw1155(deps, env, info, msg, payment)
+        }
+        TokenHookMsg::SetCw1155Bid { nft_address, token_id, quantity, expire } => {
+            let token_symbol = TOKENADDRESS.may_load(deps.storage, info.sender.as_ref())?
+                .ok_or(ContractError::WrongTokenContractError {  })?;
+            if quantity.is_zero() {
+                return Err(ContractError::WrongConfig {  })
+            }
+            let bid = Cw1155Bid {
+                collection: nft_address,
+                token_id,
+                bidder: rcv_msg.sender,
+                unit_price: Asset { denom: token_symbol, amount: rcv_msg.amount / quantity },
+                token_address: Some(info.sender.to_string()),
+                quantity,
+                expires_at: expire,
+            };
+            execute_set_cw1155_bid(deps, env, bid, rcv_msg.amount)
+        }
+    }
+}
+
+fn execute_set_reward_config(
+    deps: DepsMut,
+    env:Env,
+    info: MessageInfo,
+    token_address: String,
+    epoch_seconds: u64,
+) -> Result<Response, ContractError> {
+    let state = CONFIG.load(deps.storage)?;
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    deps.api.addr_validate(&token_address)?;
+    if epoch_seconds == 0 {
+        return Err(ContractError::WrongConfig {});
+    }
+
+    let start_time = match REWARD_CONFIG.may_load(deps.storage)? {
+        Some(config) => {
+            let pool = REWARD_POOL.may_load(deps.storage)?.unwrap_or_default();
+            if config.token_address != token_address && !pool.is_zero() {
+                return Err(ContractError::RewardPoolNotEmpty {});
+            }
+            //accrued rewards are paid out in the token they were funded with
+            let outstanding = REWARD_OUTSTANDING.may_load(deps.storage)?.unwrap_or_default();
+            if config.token_address != token_address && !outstanding.is_zero() {
+                return Err(ContractError::UnclaimedRewards {});
+            }
+            config.start_time
+        }
+        None => env.block.time.seconds(),
+    };
+    REWARD_CONFIG.save(deps.storage, &RewardConfig { token_address: token_address.clone(), epoch_seconds, start_time })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set reward config")
+        .add_event(Event::new("reward-config-set")
+            .add_attribute("token_address", token_address)
+            .add_attribute("epoch_seconds", epoch_seconds.to_string())))
+}
+
+fn execute_set_reward_rate(
+    deps: DepsMut,
+    _env:Env,
+    info: MessageInfo,
+    collection: String,
+    denom: String,
+    rate: Decimal,
+) -> Result<Response, ContractError> {
+    let state = CONFIG.load(deps.storage)?;
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if COLLECTIONINFO.may_load(deps.storage, &collection)?.is_none() {
+        return Err(ContractError::WrongCollection {});
+    }
+
+    if rate.is_zero() {
+        REWARD_RATES.remove(deps.storage, (&collection, &denom));
+    } else {
+        REWARD_RATES.save(deps.storage, (&collection, &denom), &rate)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set reward rate")
+        .add_event(Event::new("reward-rate-set")
+            .add_attribute("collection", collection)
+            .add_attribute("denom", denom)
+            .add_attribute("rate", rate.to_string())))
+}
+
+fn execute_claim_rewards(
+    deps: DepsMut,
+    env:Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = REWARD_CONFIG.may_load(deps.storage)?.ok_or(ContractError::NoRewardProgram {})?;
+    let claimer = info.sender.to_string();
+
+    let ended = EPOCH_REWARDS
+        .prefix(&claimer)
+        .range(deps.storage, None, Some(Bound::inclusive(env.block.time.seconds())), cosmwasm_std::Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut amount = Uint128::zero();
+    for (epoch_end, reward) in ended {
+        EPOCH_REWARDS.remove(deps.storage, (&claimer, epoch_end));
+        amount += reward;
+    }
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    let outstanding = REWARD_OUTSTANDING.may_load(deps.storage)?.unwrap_or_default();
+    REWARD_OUTSTANDING.save(deps.storage, &outstanding.saturating_sub(amount))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "claim rewards")
+        .add_event(Event::new("rewards-claimed")
+            .add_attribute("recipient", claimer.clone())
+            .add_attribute("amount", amount.to_string()))
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.token_address,
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer { recipient: claimer, amount })?,
+            funds: vec![],
+        })))
+}
+
+/// Removes expired asks, bids and collection bids, returning NFTs and escrow to their owners.
+/// Anyone can call it, every removed order counts against `limit`. An order expired for longer than
+/// the grace period earns a share of its escrow in the bounty denom, at most the bounty, while the pool lasts.
+/// Asks escrow no funds, and orders the caller placed or sells into earn nothing
+fn execute_cleanup_expired(
+    deps: DepsMut,
+    env:Env,
+    info: MessageInfo,
+    limit: Option<u32>
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut remaining = limit.unwrap_or(MAX_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    let mut cleanup_events: Vec<Event> = Vec::new();
+    let mut removed: u128 = 0;
+    let mut earned = Uint128::zero();
+    let bounty = CLEANUP_BOUNTY.may_load(deps.storage)?;
+    let reward = |parties: &[&str], expires_at: Timestamp, escrow: &Asset, token_address: &Option<String>| match &bounty {
+        Some(bounty) if !parties.contains(&info.sender.as_str())
+            && expires_at.plus_seconds(CLEANUP_GRACE_SECONDS) <= env.block.time
+            && token_address.is_none()
+            && escrow.denom == bounty.denom => (escrow.amount * CLEANUP_BOUNTY_SHARE).min(bounty.amount),
+        _ => Uint128::zero()
+    };
+
+    let expired_asks = asks()
+        .idx
+        .expires_at
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .take_while(|item| item.as_ref().map_or(true, |(_, ask)| ask.is_expired(&env.block)))
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (_, ask) in expired_asks {
+        //bids on the token are refunded first, the ask goes once none are left within the limit
+        let existing_bids_token = query_bids(deps.as_ref(), ask.collection.clone(), ask.token_id.clone(), None, Some(MAX_QUERY_LIMIT))?;
+        let ask_fits = existing_bids_token.bids.len() < remaining;
+        for bid in existing_bids_token.bids.into_iter().take(remaining) {
+            bids().remove(deps.storage, (bid.collection.clone(), bid.token_id.clone(), bid.bidder.clone()))?;
+            remove_counter_offer(deps.storage, &bid.collection, &bid.token_id, &bid.bidder)?;
+            push_refund(&mut messages, &bid.bidder, &bid.list_price, &bid.token_address)?;
+            cleanup_events.push(events::bid_removed(&bid));
+            removed += 1;
+            remaining -= 1;
+            earned = earned.checked_add(reward(&[&bid.bidder, &bid.seller], bid.expires_at, &bid.list_price, &bid.token_address)).map_err(StdError::from)?;
+        }
+        if !ask_fits {
+            break;
+        }
+
+        asks().remove(deps.storage, ask_key(&ask.collection, &ask.token_id))?;
+        remove_counter_offers(deps.storage, &ask.collection, &ask.token_id)?;
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: ask.collection.clone(),
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: ask.seller.clone(),
+                token_id: ask.token_id.clone() })?,
+            funds: vec![] }));
+        cleanup_events.push(events::ask_removed(&ask));
+        removed += 1;
+        remaining -= 1;
+    }
+
+    let expired_bids = bids()
+        .idx
+        .expires_at
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .take_while(|item| item.as_ref().map_or(true, |(_, bid)| bid.is_expired(&env.block)))
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= expired_bids.len();
+    for (_, bid) in expired_bids {
+        bids().remove(deps.storage, (bid.collection.clone(), bid.token_id.clone(), bid.bidder.clone()))?;
+        remove_counter_offer(deps.storage, &bid.collection, &bid.token_id, &bid.bidder)?;
+        push_refund(&mut messages, &bid.bidder, &bid.list_price, &bid.token_address)?;
+        cleanup_events.push(events::bid_removed(&bid));
+        removed += 1;
+        earned = earned.checked_add(reward(&[&bid.bidder, &bid.seller], bid.expires_at, &bid.list_price, &bid.token_address)).map_err(StdError::from)?;
+    }
+
+    let expired_collection_bids = collection_bids()
+        .idx
+        .expires_at
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .take_while(|item| item.as_ref().map_or(true, |(_, bid)| bid.is_expired(&env.block)))
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= expired_collection_bids.len();
+    for (_, collection_bid) in expired_collection_bids {
+        collection_bids().remove(deps.storage, collection_bid_key(&collection_bid.collection, &collection_bid.bidder))?;
+        push_refund(&mut messages, &collection_bid.bidder, &collection_bid.list_price, &collection_bid.token_address)?;
+        cleanup_events.push(events::collection_bid_removed(&collection_bid));
+        removed += 1;
+        earned = earned.checked_add(reward(&[&collection_bid.bidder], collection_bid.expires_at, &collection_bid.list_price, &collection_bid.token_address)).map_err(StdError::from)?;
+    }
+
+    let expired_offers = offers()
+        .idx
+        .expires_at
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .take_while(|item| item.as_ref().map_or(true, |(_, offer)| offer.is_expired(&env.block)))
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= expired_offers.len();
+    for (_, offer) in expired_offers {
+        offers().remove(deps.storage, bid_key(&offer.collection, &offer.token_id, &offer.bidder))?;
+        push_refund(&mut messages, &offer.bidder, &offer.price, &offer.token_address)?;
+        cleanup_events.push(events::offer_removed(&offer));
+        removed += 1;
+        earned = earned.checked_add(reward(&[&offer.bidder], offer.expires_at, &offer.price, &offer.token_address)).map_err(StdError::from)?;
+    }
+
+    let expired_cw1155_asks = cw1155_asks()
+        .idx
+        .expires_at
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .take_while(|item| item.as_ref().map_or(true, |(_, ask)| ask.is_expired(&env.block)))
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    remaining -= expired_cw1155_asks.len();
+    for (_, ask) in expired_cw1155_asks {
+        cw1155_asks().remove(deps.storage, bid_key(&ask.collection, &ask.token_id, &ask.seller))?;
+        messages.push(cw1155_send_msg(&ask.collection, &ask.seller, &ask.token_id, ask.quantity, &env)?);
+        cleanup_events.push(events::cw1155_ask_removed(&ask));
+        removed += 1;
+    }
+
+    let expired_cw1155_bids = cw1155_bids()
+        .idx
+        .expires_at
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .take_while(|item| item.as_ref().map_or(true, |(_, bid)| bid.is_expired(&env.block)))
+        .take(remaining)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (_, bid) in expired_cw1155_bids {
+        cw1155_bids().remove(deps.storage, bid_key(&bid.collection, &bid.token_id, &bid.bidder))?;
+        let escrow = cw1155_bid_escrow(&bid);
+        push_refund(&mut messages, &bid.bidder, &escrow, &bid.token_address)?;
+        cleanup_events.push(events::cw1155_bid_removed(&bid));
+        removed += 1;
+        earned = earned.checked_add(reward(&[&bid.bidder], bid.expires_at, &escrow, &bid.token_address)).map_err(StdError::from)?;
+    }
+
+    //The bounty is capped by what is left in the pool
+    let mut bounty_paid = Uint128::zero();
+    if let Some(bounty) = bounty {
+        let pool = CLEANUP_POOL.may_load(deps.storage)?.unwrap_or_default();
+        bounty_paid = earned.min(pool);
+        if !bounty_paid.is_zero() {
+            CLEANUP_POOL.save(deps.storage, &(pool - bounty_paid))?;
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![Coin{ denom: bounty.denom, amount: bounty_paid }] }));
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "cleanup expired")
+        .add_attribute("removed", removed.to_string())
+        .add_events(cleanup_events)
+        .add_event(Event::new("expired-orders-cleaned")
+            .add_attribute("caller", info.sender.to_string())
+            .add_attribute("removed", removed.to_string())
+            .add_attribute("bounty", bounty_paid.to_string()))
+        .add_messages(messages))
+}
+
+fn execute_set_cleanup_bounty(
     deps: DepsMut,
     _env:Env,
     info: MessageInfo,
-    address: String,
+    bounty: Option<Asset>
 ) -> Result<Response, ContractError> {
-    let mut state = CONFIG.load(deps.storage)?;
-
-    if state.admin != info.sender.to_string() {
+    let state = CONFIG.load(deps.storage)?;
+    if state.owner != info.sender {
         return Err(ContractError::Unauthorized {});
     }
-    deps.api.addr_validate(&address)?;
-    state.owner = address;
-    CONFIG.save(deps.storage,&state)?;
-    Ok(Response::default())
-}
 
+    let pool = CLEANUP_POOL.may_load(deps.storage)?.unwrap_or_default();
+    let current_denom = CLEANUP_BOUNTY.may_load(deps.storage)?.map(|bounty| bounty.denom);
+    if !pool.is_zero() && current_denom != bounty.as_ref().map(|bounty| bounty.denom.clone()) {
+        return Err(ContractError::CleanupPoolNotEmpty {});
+    }
 
-fn execute_change_admin(
+    let event = Event::new("cleanup-bounty-set");
+    let event = match bounty {
+        Some(bounty) => {
+            if COINDENOM.may_load(deps.storage, &bounty.denom)?.is_none() {
+                return Err(ContractError::WrongCoinDenom {});
+            }
+            CLEANUP_BOUNTY.save(deps.storage, &bounty)?;
+            event
+                .add_attribute("denom", bounty.denom)
+                .add_attribute("amount", bounty.amount.to_string())
+        }
+        None => {
+            CLEANUP_BOUNTY.remove(deps.storage);
+            event
+        }
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "set cleanup bounty")
+        .add_event(event))
+}
+
+fn execute_fund_cleanup_bounty(
     deps: DepsMut,
     _env:Env,
     info: MessageInfo,
-    address: String,
 ) -> Result<Response, ContractError> {
-    let mut state = CONFIG.load(deps.storage)?;
-
-    if state.admin != info.sender.to_string() {
-        return Err(ContractError::Unauthorized {});
+    let bounty = CLEANUP_BOUNTY.may_load(deps.storage)?.ok_or(ContractError::NoCleanupBounty {})?;
+    let amount = info
+        .funds
+        .iter()
+        .find(|c| c.denom == bounty.denom)
+        .map(|c| c.amount)
+        .unwrap_or_else(Uint128::zero);
+    if amount.is_zero() || info.funds.len() != 1 {
+        return Err(ContractError::NotEnoughFunds {});
     }
-    deps.api.addr_validate(&address)?;
-    state.admin = address;
-    CONFIG.save(deps.storage,&state)?;
-    Ok(Response::default())
+
+    let pool = CLEANUP_POOL.may_load(deps.storage)?.unwrap_or_default() + amount;
+    CLEANUP_POOL.save(deps.storage, &pool)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "fund cleanup bounty")
+        .add_event(Event::new("cleanup-bounty-funded")
+            .add_attribute("funder", info.sender.to_string())
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("pool", pool.to_string())))
 }
 
 fn execute_set_tvl(
@@ -1505,12 +3509,16 @@ fn execute_set_tvl(
     }
 
    
+    let count = tvls.len();
     for collection_tvl in tvls{
          let tvl_key = tvl_key(&address, &collection_tvl.denom);
          tvl().save(deps.storage,tvl_key.clone(), &collection_tvl)?;
     }
 
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_event(Event::new("tvl-set")
+            .add_attribute("collection", address)
+            .add_attribute("count", count.to_string())))
 }
 
 fn execute_set_offerings(
@@ -1525,6 +3533,7 @@ fn execute_set_offerings(
     if state.owner != info.sender.to_string() {
         return Err(ContractError::Unauthorized {});
     }
+    let count = offerings.len();
     for offering in offerings{
     
         let new_ask = Ask{
@@ -1532,14 +3541,21 @@ fn execute_set_offerings(
             seller: offering.seller,
             list_price: offering.list_price,
             expires_at: Timestamp::from_seconds(env.block.time.seconds() + 259200),
-            collection: nft_address.clone()
+            collection: nft_address.clone(),
+            reserved_for: None,
+            buy_now_price: None,
+            reserve_price: None,
+            min_bid_increment: None
         };
 
         let ask_key = ask_key(&nft_address, &offering.token_id);
         asks().save(deps.storage, ask_key, &new_ask)?;
     }
     
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_event(Event::new("offerings-set")
+            .add_attribute("collection", nft_address)
+            .add_attribute("count", count.to_string())))
 }
 
 
@@ -1558,12 +3574,136 @@ fn execute_history(
 
     // let mut count = 0;
     
-    for history in histories{
-        let sale_history_key = sale_history_key(&address, &history.token_id, history.time);
+    let count = histories.len();
+    for mut history in histories{
+        history.seq = next_sale_seq(deps.storage)?;
+        let sale_history_key = sale_history_key(&address, &history.token_id, history.time, history.seq);
         sale_history().save(deps.storage, sale_history_key, &history)?;
     }
    
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_event(Event::new("sale-history-set")
+            .add_attribute("collection", address)
+            .add_attribute("count", count.to_string())))
+}
+
+fn execute_migrate_sale_history(
+    deps: DepsMut,
+    _env:Env,
+    info:MessageInfo,
+    limit: Option<u32>
+) -> Result<Response, ContractError> {
+    let  state = CONFIG.load(deps.storage)?;
+
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let limit = limit.unwrap_or(MAX_QUERY_LIMIT) as usize;
+    let legacy = legacy_sale_history()
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let migrated = legacy.len();
+    for (key, mut history) in legacy{
+        legacy_sale_history().remove(deps.storage, key.clone())?;
+        //numbered in legacy key order, the time in the key keeps the history ordered by time
+        history.seq = next_sale_seq(deps.storage)?;
+        let sale_history_key = sale_history_key(&key.0, &key.1, key.2, history.seq);
+        sale_history().save(deps.storage, sale_history_key, &history)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "Migrate sale history")
+        .add_attribute("migrated", migrated.to_string())
+        .add_event(Event::new("sale-history-migrated").add_attribute("migrated", migrated.to_string())))
+}
+
+fn execute_backfill_expiry_indexes(
+    deps: DepsMut,
+    _env:Env,
+    info:MessageInfo,
+    limit: Option<u32>
+) -> Result<Response, ContractError> {
+    let  state = CONFIG.load(deps.storage)?;
+
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut remaining = limit.unwrap_or(MAX_QUERY_LIMIT) as usize;
+    let mut progress = EXPIRY_BACKFILL.may_load(deps.storage)?.unwrap_or_default();
+    let mut backfilled = 0;
+    while remaining > 0 && progress.book != ExpiryBackfillBook::Done {
+        let start = progress.last_key.clone().map(|key| key.to_vec());
+        //saving an order again rewrites all of its index entries
+        let (page_len, last_key) = match progress.book {
+            ExpiryBackfillBook::Asks => {
+                let page = asks()
+                    .range_raw(deps.storage, start.map(Bound::ExclusiveRaw), None, cosmwasm_std::Order::Ascending)
+                    .take(remaining)
+                    .collect::<StdResult<Vec<_>>>()?;
+                for (_, ask) in &page {
+                    asks().save(deps.storage, ask_key(&ask.collection, &ask.token_id), ask)?;
+                }
+                (page.len(), page.last().map(|(key, _)| key.clone()))
+            }
+            ExpiryBackfillBook::Bids => {
+                let page = bids()
+                    .range_raw(deps.storage, start.map(Bound::ExclusiveRaw), None, cosmwasm_std::Order::Ascending)
+                    .take(remaining)
+                    .collect::<StdResult<Vec<_>>>()?;
+                for (_, bid) in &page {
+                    bids().save(deps.storage, bid_key(&bid.collection, &bid.token_id, &bid.bidder), bid)?;
+                }
+                (page.len(), page.last().map(|(key, _)| key.clone()))
+            }
+            ExpiryBackfillBook::CollectionBids => {
+                let page = collection_bids()
+                    .range_raw(deps.storage, start.map(Bound::ExclusiveRaw), None, cosmwasm_std::Order::Ascending)
+                    .take(remaining)
+                    .collect::<StdResult<Vec<_>>>()?;
+                for (_, collection_bid) in &page {
+                    collection_bids().save(deps.storage, collection_bid_key(&collection_bid.collection, &collection_bid.bidder), collection_bid)?;
+                }
+                (page.len(), page.last().map(|(key, _)| key.clone()))
+            }
+            ExpiryBackfillBook::Done => (0, None)
+        };
+
+        backfilled += page_len;
+        if page_len < remaining {
+            //this book is done, move on to the next one
+            progress = ExpiryBackfill {
+                book: match progress.book {
+                    ExpiryBackfillBook::Asks => ExpiryBackfillBook::Bids,
+                    ExpiryBackfillBook::Bids => ExpiryBackfillBook::CollectionBids,
+                    _ => ExpiryBackfillBook::Done
+                },
+                last_key: None
+            };
+        } else {
+            progress.last_key = last_key.map(Binary::from);
+        }
+        remaining -= page_len;
+    }
+    EXPIRY_BACKFILL.save(deps.storage, &progress)?;
+
+    let done = progress.book == ExpiryBackfillBook::Done;
+    Ok(Response::new()
+        .add_attribute("action", "Backfill expiry indexes")
+        .add_attribute("backfilled", backfilled.to_string())
+        .add_attribute("done", done.to_string())
+        .add_event(Event::new("expiry-indexes-backfilled")
+            .add_attribute("backfilled", backfilled.to_string())
+            .add_attribute("done", done.to_string())))
+}
+
+fn next_sale_seq(store: &mut dyn Storage) -> StdResult<u64> {
+    let seq = SALE_SEQ.may_load(store)?.unwrap_or_default() + 1;
+    SALE_SEQ.save(store, &seq)?;
+    Ok(seq)
 }
 
 
@@ -1587,7 +3727,8 @@ fn execute_bid_limit(
 
     Ok(Response::new()
         .add_attribute("action", "set bid limit")
-        .add_attribute("bid_limit", bid_limit.to_string()))
+        .add_attribute("bid_limit", bid_limit.to_string())
+        .add_event(Event::new("bid-limit-set").add_attribute("bid_limit", bid_limit.to_string())))
 }
 
 
@@ -1607,6 +3748,13 @@ fn execute_withdraw_coin(
         return Err(ContractError::Unauthorized {});
     }
 
+    let event = Event::new("funds-withdrawn")
+        .add_attribute("recipient", state.owner.clone())
+        .add_attribute("token_address", token_address.clone())
+        .add_attribute("token_amount", token_amount.to_string())
+        .add_attribute("denom", coin_denom.clone())
+        .add_attribute("amount", coin_amount.to_string());
+
     let mut messages: Vec<CosmosMsg> = Vec::new();
     if token_amount > Uint128::new(0){
       messages.push(CosmosMsg::Wasm(WasmMsg::Execute { 
@@ -1630,11 +3778,159 @@ fn execute_withdraw_coin(
     }
 
     Ok(Response::new()
+        .add_event(event)
         .add_messages(messages)
       )
 }
 
 
+/// Auction bids must reach the reserve price and beat the highest live bid in the same denom by the minimum increment.
+/// Called once the bidder's previous bid on the token has been removed
+fn validate_bid_rules(
+    deps: Deps,
+    env: &Env,
+    ask: &Ask,
+    collection_info: &CollectionInfo,
+    bidder: &str,
+    bid_price: &Asset
+) -> Result<(), ContractError> {
+    //without a reserve price the collection minimum applies
+    let reserve_price = match ask.reserve_price {
+        Some(reserve_price) => Some(reserve_price),
+        None if !collection_info.min_bid_portion.is_zero() => Some(ask.list_price.amount * collection_info.min_bid_portion),
+        None => None
+    };
+    if let Some(reserve_price) = reserve_price {
+        if bid_price.denom != ask.list_price.denom || bid_price.amount < reserve_price {
+            return Err(ContractError::BidBelowReserve {  })
+        }
+    }
+
+    let min_bid_increment = ask.min_bid_increment.unwrap_or(collection_info.min_bid_increment);
+    if min_bid_increment.is_zero() {
+        return Ok(())
+    }
+    //the bidder's own bid is replaced, so only the others count
+    let top_bid = bids()
+        .idx
+        .token_price
+        .sub_prefix(((ask.collection.clone(), ask.token_id.clone()), bid_price.denom.clone()))
+        .range(deps.storage, None, None, cosmwasm_std::Order::Descending)
+        .map(|item| item.map(|(_, bid)| bid))
+        .find(|item| item.as_ref().map_or(true, |bid| !bid.is_expired(&env.block) && bid.bidder != bidder))
+        .transpose()?;
+    if let Some(top_bid) = top_bid {
+        let min_amount = top_bid.list_price.amount + top_bid.list_price.amount * min_bid_increment;
+        if bid_price.amount < min_amount {
+            return Err(ContractError::BidIncrementTooLow {  })
+        }
+    }
+    Ok(())
+}
+
+/// Picks the bid to evict from a token that reached the bid limit: any expired bid first,
+/// otherwise the lowest live bid in the new bid's denom, as long as the new bid beats it
+fn find_evictable_bid(
+    deps: Deps,
+    env: &Env,
+    existing_bids: &[Bid],
+    collection: &str,
+    token_id: &str,
+    bid_price: &Asset
+) -> Result<Bid, ContractError> {
+    if let Some(expired) = existing_bids.iter().find(|bid| bid.is_expired(&env.block)) {
+        return Ok(expired.clone())
+    }
+
+    let lowest = bids()
+        .idx
+        .token_price
+        .sub_prefix(((collection.to_string(), token_id.to_string()), bid_price.denom.clone()))
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .next()
+        .transpose()?;
+    match lowest {
+        Some((_, bid)) if bid.list_price.amount < bid_price.amount => Ok(bid),
+        _ => Err(ContractError::BidCountExpired {  })
+    }
+}
+
+/// Total held in escrow for a cw1155 bid
+fn cw1155_bid_escrow(bid: &Cw1155Bid) -> Asset {
+    Asset { denom: bid.unit_price.denom.clone(), amount: bid.unit_price.amount * bid.quantity }
+}
+
+/// Sends escrowed cw1155 units out of the contract
+fn cw1155_send_msg(collection: &str, recipient: &str, token_id: &str, quantity: Uint128, env: &Env) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: collection.to_string(),
+        msg: to_json_binary(&Cw1155ExecuteMsg::SendFrom {
+            from: env.contract.address.to_string(),
+            to: recipient.to_string(),
+            token_id: token_id.to_string(),
+            value: quantity,
+            msg: None })?,
+        funds: vec![] }))
+}
+
+/// Returns escrowed funds, in cw20 tokens when `token_address` is set and in coins otherwise
+fn refund_msg(recipient: &str, price: &Asset, token_address: &Option<String>) -> StdResult<CosmosMsg> {
+    match token_address {
+        Some(token_address) => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: token_address.clone(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: price.amount })?,
+            funds: vec![] })),
+        None => Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin{ denom: price.denom.clone(), amount: price.amount }] }))
+    }
+}
+
+/// Queues the refund of an escrow, skipping empty ones since a zero amount transfer would revert
+fn push_refund(messages: &mut Vec<CosmosMsg>, recipient: &str, price: &Asset, token_address: &Option<String>) -> StdResult<()> {
+    if !price.amount.is_zero() {
+        messages.push(refund_msg(recipient, price, token_address)?);
+    }
+    Ok(())
+}
+
+/// Finders are paid out of the seller proceeds, so the buyer and the seller of a listed token can not be its finder.
+/// Sellers of offers and collection bids are only known at settlement, where `pay_out` skips them
+fn validate_finder(store: &dyn Storage, finder: &str, buyer: &str, nft_address: &str, token_id: &Option<String>) -> Result<(), ContractError> {
+    if finder == buyer {
+        return Err(ContractError::InvalidFinder {  })
+    }
+    if let Some(token_id) = token_id {
+        if let Some(ask) = asks().may_load(store, ask_key(&nft_address.to_string(), token_id))? {
+            if ask.seller == finder {
+                return Err(ContractError::InvalidFinder {  })
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A list price is either a registered coin, or the denom registered for `token_address`
+fn validate_list_price(store: &dyn Storage, list_price: &Asset, token_address: &Option<String>) -> Result<(), ContractError> {
+    match token_address {
+        None => {
+            match COINDENOM.may_load(store, &list_price.denom)? {
+                Some(true) => Ok(()),
+                _ => Err(ContractError::WrongCoinDenom {  })
+            }
+        }
+        //Validate Configuration, token_address can not be existed if the list price is set as coin
+        Some(token_address) => {
+            match TOKENADDRESS.may_load(store, token_address)? {
+                Some(denom) if denom == list_price.denom => Ok(()),
+                _ => Err(ContractError::WrongTokenContractError {  })
+            }
+        }
+    }
+}
+
 fn store_ask(store: &mut dyn Storage, ask: &Ask) -> StdResult<()> {
     asks().save(store, ask_key(&ask.collection, &ask.token_id), ask)
 }
@@ -1648,6 +3944,181 @@ fn store_bid(store: &mut dyn Storage, bid: &Bid) -> StdResult<()> {
     )
 }
 
+/// Finds the highest unexpired collection bid that pays for the ask in the same asset and meets its list price.
+/// Reserved asks only match a bid from the reserved buyer, and the seller's own bid is never matched
+fn find_best_collection_bid(
+    store: &dyn Storage,
+    env: &Env,
+    ask: &Ask,
+    token_address: &Option<String>
+) -> StdResult<Option<CollectionBid>> {
+    let bids = collection_bids()
+        .idx
+        .collection_price
+        .sub_prefix((ask.collection.clone(), ask.list_price.denom.clone()))
+        .range(
+            store,
+            Some(Bound::inclusive((ask.list_price.amount.u128(), (String::new(), String::new())))),
+            None,
+            cosmwasm_std::Order::Descending
+        );
+
+    for item in bids {
+        let (_, bid) = item?;
+        if !bid.is_expired(&env.block) && bid.token_address == *token_address && bid.bidder != ask.seller && !ask.is_reserved_for_other(&bid.bidder) {
+            return Ok(Some(bid));
+        }
+    }
+    Ok(None)
+}
+
+/// Removes the collection bid, records the sale and pays out of the bid escrow. The token must already be held by the contract
+fn settle_collection_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bid: CollectionBid,
+    seller: String,
+    token_id: String,
+    messages: &mut Vec<CosmosMsg>
+) -> Result<Vec<SubMsg>, ContractError> {
+    collection_bids().remove(deps.storage, collection_bid_key(&bid.collection, &bid.bidder))?;
+    let royalty_portion = COLLECTIONINFO.load(deps.storage, &bid.collection)?.royalty_portion;
+
+    let hook_msgs = update_sale_history_tvl(
+        deps.storage,
+        env,
+        info,
+        seller.clone(),
+        bid.bidder.clone(),
+        bid.collection.clone(),
+        token_id.clone(),
+        bid.list_price.clone(),
+        None
+    )?;
+
+    distribute_money(
+        deps.storage,
+        bid.collection,
+        royalty_portion,
+        Payout {
+            seller,
+            price: bid.list_price,
+            token_address: bid.token_address,
+            finder: bid.finder,
+        },
+        bid.bidder,
+        token_id,
+        messages
+    )?;
+    Ok(hook_msgs)
+}
+
+/// Hands the NFT to the buyer, or to the ICS-721 bridge to release it to `origin_receiver` on the origin chain
+fn nft_delivery_msg(
+    store: &dyn Storage,
+    env: &Env,
+    collection: &str,
+    token_id: &str,
+    buyer: &str,
+    origin_receiver: Option<String>
+) -> Result<CosmosMsg, ContractError> {
+    let msg = match origin_receiver {
+        None => to_json_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: buyer.to_string(),
+            token_id: token_id.to_string() })?,
+        Some(receiver) => {
+            let origin = COLLECTIONINFO.load(store, collection)?.ics721.ok_or(ContractError::WrongConfig {  })?;
+            to_json_binary(&Cw721ExecuteMsg::SendNft {
+                contract: origin.bridge,
+                token_id: token_id.to_string(),
+                msg: to_json_binary(&Ics721OutgoingMsg {
+                    receiver,
+                    channel_id: origin.channel_id,
+                    timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(ICS721_TIMEOUT_SECONDS)),
+                    memo: None })? })?
+        }
+    };
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: collection.to_string(),
+        msg,
+        funds: vec![] }))
+}
+
+/// Buyer side of a fixed price sale, paid with `price` in the coin or `token_address`
+struct Purchase {
+    buyer: String,
+    price: Asset,
+    token_address: Option<String>,
+    finder: Option<String>,
+    origin_receiver: Option<String>,
+}
+
+/// Closes an ask at the purchase price: removes it, refunds every bid on the token, records the sale and pays out.
+/// Used by fixed price purchases and by auction bids that reach the buy-now price
+fn settle_fixed_price_sale(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    ask: Ask,
+    purchase: Purchase,
+    royalty_portion: Decimal,
+    messages: &mut Vec<CosmosMsg>
+) -> Result<Vec<SubMsg>, ContractError> {
+    let Purchase { buyer, price, token_address, finder, origin_receiver } = purchase;
+    let delivery = nft_delivery_msg(deps.storage, &env, &ask.collection, &ask.token_id, &buyer, origin_receiver)?;
+    let existing_bids_token = query_bids(deps.as_ref(), ask.collection.clone(), ask.token_id.clone(), None, Some(MAX_QUERY_LIMIT))?;
+    asks().remove(deps.storage, ask_key(&ask.collection, &ask.token_id))?;
+    remove_counter_offers(deps.storage, &ask.collection, &ask.token_id)?;
+
+    //bid information for this token_id;
+    for bid in existing_bids_token.bids{
+        match bid.token_address{
+            Some(token_address) =>{
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute { 
+                    contract_addr: token_address,
+                    msg: to_json_binary(&Cw20ExecuteMsg::Transfer { 
+                        recipient: bid.bidder.clone(),
+                        amount: bid.list_price.amount })?,
+                    funds: vec![] }));
+            }   
+            None =>{
+                messages.push(CosmosMsg::Bank(BankMsg::Send {
+                    to_address: bid.bidder.clone(),
+                    amount: vec![Coin{denom: bid.list_price.denom, amount: bid.list_price.amount}] }));               
+            }
+        }
+        bids().remove(deps.storage, (ask.collection.clone(), ask.token_id.clone(), bid.bidder))?;                
+    }
+
+    let hook_msgs = update_sale_history_tvl(
+        deps.storage,
+        env,
+        info,
+        ask.seller.clone(),
+        buyer.clone(),
+        ask.collection.clone(),
+        ask.token_id.clone(),
+        price.clone(),
+        None
+    )?;
+
+    pay_out(
+        deps.storage,
+        &ask.collection,
+        royalty_portion,
+        Payout {
+            seller: ask.seller,
+            price,
+            token_address,
+            finder,
+        },
+        messages
+    )?;
+    messages.push(delivery);
+    Ok(hook_msgs)
+}
+
 fn update_sale_history_tvl(
      store:& mut dyn Storage,
      env:Env,
@@ -1656,9 +4127,10 @@ fn update_sale_history_tvl(
      bidder:String,
      nft_address: String,
      token_id: String,
-     list_price: Asset
-) -> StdResult<()> {
-     //Add sale info created by fixed price sale
+     list_price: Asset,
+     quantity: Option<Uint128>
+) -> StdResult<Vec<SubMsg>> {
+     //Add sale info created by fixed price sale, cw1155 sales are recorded at their unit price
         let new_sale_info = SaleInfo { 
             from: seller,
             to: bidder.clone(), 
@@ -1666,45 +4138,288 @@ fn update_sale_history_tvl(
             amount: list_price.amount, 
             time: env.block.time.seconds(),
             collection:nft_address.clone(),
-            token_id:token_id.clone()
+            token_id:token_id.clone(),
+            seq: next_sale_seq(store)?,
+            quantity
         };
+        let total = Asset { denom: list_price.denom.clone(), amount: list_price.amount * quantity.unwrap_or(Uint128::new(1)) };
         let crr_time = env.block.time.seconds();
-        let sale_history_key = sale_history_key(&nft_address, &token_id, crr_time);
+        let sale_history_key = sale_history_key(&nft_address, &token_id, crr_time, new_sale_info.seq);
         sale_history().save(store, sale_history_key, &new_sale_info)?;
+        record_activity(
+            store,
+            &env,
+            ActivityEntry {
+                activity_type: ActivityType::Sale,
+                collection: nft_address.clone(),
+                token_id: Some(token_id.clone()),
+                actor: bidder.clone(),
+                counterparty: Some(new_sale_info.from.clone()),
+                price: Some(list_price.clone()),
+            }
+        )?;
 
         //update the TVL
-        let denom = list_price.denom;
+        let denom = list_price.denom.clone();
         let tvl_key = tvl_key(&nft_address.clone(), &denom);
         let  crr_tvl = tvl().may_load(store, tvl_key.clone())?;
         match crr_tvl{
             Some(mut crr_tvl) => {
-                crr_tvl.amount = crr_tvl.amount + list_price.amount;
+                crr_tvl.amount = crr_tvl.amount + total.amount;
                 tvl().save(store, tvl_key, &crr_tvl)?; 
             }
             None =>{
                 tvl().save(store, tvl_key, &TvlInfo { 
                     denom: denom,
-                    amount: list_price.amount,
+                    amount: total.amount,
                     collection: nft_address.clone() })?; 
             }
         }
 
+        //prices track the unit price, volumes the total paid
+        update_collection_stats(store, crr_time, &nft_address, &list_price, total.amount)?;
+        update_candle(store, CandleInterval::Hour, crr_time, &nft_address, &list_price, total.amount)?;
+        update_candle(store, CandleInterval::Day, crr_time, &nft_address, &list_price, total.amount)?;
+        //selling to yourself earns no rewards
+        if bidder != new_sale_info.from {
+            accrue_trading_rewards(store, crr_time, &nft_address, &total, &[&bidder, &new_sale_info.from])?;
+        }
+
+    prepare_hooks(store, HookType::Sale, MarketplaceHookMsg::SaleHook(SaleHookMsg {
+        collection: nft_address,
+        token_id,
+        seller: new_sale_info.from,
+        buyer: bidder,
+        price: list_price,
+        time: crr_time,
+        quantity,
+    }))
+}
+
+/// Accrues trading rewards for each party of a sale into the current epoch, as long as the pool lasts
+fn accrue_trading_rewards(
+    store: &mut dyn Storage,
+    time: u64,
+    collection: &str,
+    price: &Asset,
+    parties: &[&str]
+) -> StdResult<()> {
+    let config = match REWARD_CONFIG.may_load(store)? {
+        Some(config) => config,
+        None => return Ok(()),
+    };
+    let rate = match REWARD_RATES.may_load(store, (collection, &price.denom))? {
+        Some(rate) => rate,
+        None => return Ok(()),
+    };
+
+    let mut pool = REWARD_POOL.may_load(store)?.unwrap_or_default();
+    let mut outstanding = REWARD_OUTSTANDING.may_load(store)?.unwrap_or_default();
+    let epoch_end = config.epoch_end(time);
+    for party in parties {
+        let reward = (price.amount * rate).min(pool);
+        if reward.is_zero() {
+            break;
+        }
+        pool -= reward;
+        outstanding += reward;
+        EPOCH_REWARDS.update(store, (party, epoch_end), |accrued| -> StdResult<_> {
+            Ok(accrued.unwrap_or_default() + reward)
+        })?;
+    }
+    REWARD_OUTSTANDING.save(store, &outstanding)?;
+    REWARD_POOL.save(store, &pool)
+}
+
+/// Builds a callback to every hook registered for `hook_type`. Failures are swallowed in `reply`
+fn prepare_hooks(store: &dyn Storage, hook_type: HookType, msg: MarketplaceHookMsg) -> StdResult<Vec<SubMsg>> {
+    hook_type
+        .hooks()
+        .may_load(store)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|hook| Ok(SubMsg::reply_on_error(msg.clone().into_cosmos_msg(hook)?, HOOK_REPLY_ID).with_gas_limit(HOOK_GAS_LIMIT)))
+        .collect()
+}
+
+/// An activity log entry before it is given an id and a time
+struct ActivityEntry {
+    activity_type: ActivityType,
+    collection: String,
+    token_id: Option<String>,
+    actor: String,
+    counterparty: Option<String>,
+    price: Option<Asset>,
+}
+
+/// Appends an entry to the activity log and indexes it for the actor and the counterparty
+fn record_activity(
+    store: &mut dyn Storage,
+    env: &Env,
+    entry: ActivityEntry
+) -> StdResult<()> {
+    let ActivityEntry { activity_type, collection, token_id, actor, counterparty, price } = entry;
+    let id = ACTIVITY_SEQ.may_load(store)?.unwrap_or_default() + 1;
+    ACTIVITY_SEQ.save(store, &id)?;
+
+    USER_ACTIVITIES.save(store, (&actor, id), &true)?;
+    if let Some(counterparty) = &counterparty {
+        if *counterparty != actor {
+            USER_ACTIVITIES.save(store, (counterparty, id), &true)?;
+        }
+    }
+
+    let activity = Activity {
+        id,
+        activity_type,
+        actor,
+        counterparty,
+        collection,
+        token_id,
+        price,
+        time: env.block.time.seconds(),
+    };
+    activities().save(store, id, &activity)
+}
+
+fn update_collection_stats(
+    store: &mut dyn Storage,
+    crr_time: u64,
+    nft_address: &str,
+    list_price: &Asset,
+    volume: Uint128
+) -> StdResult<()> {
+    let denom = list_price.denom.as_str();
+
+    let mut stats = COLLECTION_STATS.may_load(store, (nft_address, denom))?.unwrap_or(CollectionStats {
+        collection: nft_address.to_string(),
+        denom: denom.to_string(),
+        sale_count: 0,
+        last_sale_price: Uint128::zero(),
+        last_sale_time: 0,
+        all_time_high: Uint128::zero(),
+    });
+    stats.sale_count += 1;
+    stats.last_sale_price = list_price.amount;
+    stats.last_sale_time = crr_time;
+    if list_price.amount > stats.all_time_high {
+        stats.all_time_high = list_price.amount;
+    }
+    COLLECTION_STATS.save(store, (nft_address, denom), &stats)?;
+
+    //Add the sale to its hourly volume bucket
+    let bucket = crr_time / VOLUME_BUCKET_SECONDS;
+    VOLUME_BUCKETS.update(store, (nft_address, denom, bucket), |bucket_volume| -> StdResult<_> {
+        Ok(bucket_volume.unwrap_or_default() + volume)
+    })?;
+
+    //Prune buckets which have fallen out of the 30 day window
+    let cutoff = crr_time.saturating_sub(30 * DAY_SECONDS) / VOLUME_BUCKET_SECONDS;
+    let stale_buckets = VOLUME_BUCKETS
+        .prefix((nft_address, denom))
+        .keys(store, None, Some(Bound::exclusive(cutoff)), cosmwasm_std::Order::Ascending)
+        .take(MAX_QUERY_LIMIT as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+    for stale_bucket in stale_buckets {
+        VOLUME_BUCKETS.remove(store, (nft_address, denom, stale_bucket));
+    }
+
+    Ok(())
+}
+
+fn update_candle(
+    store: &mut dyn Storage,
+    interval: CandleInterval,
+    crr_time: u64,
+    nft_address: &str,
+    list_price: &Asset,
+    volume: Uint128
+) -> StdResult<()> {
+    let start_time = crr_time - crr_time % interval.seconds();
+    let price = list_price.amount;
+
+    interval.candles().update(store, (nft_address, &list_price.denom, start_time), |candle| -> StdResult<_> {
+        match candle {
+            Some(mut candle) => {
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.close = price;
+                candle.volume += volume;
+                candle.sale_count += 1;
+                Ok(candle)
+            }
+            None => Ok(Candle {
+                start_time,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume,
+                sale_count: 1,
+            })
+        }
+    })?;
+
     Ok(())
 }
 
 fn distribute_money(
-    deps: Deps,
+    store: &mut dyn Storage,
     nft_address: String,
     royalty_portion: Decimal,
-    seller: String,
+    payout: Payout,
     bidder: String,
-    list_price: Asset,
-    token_address: Option<String>,
     token_id: String,
     messages:& mut Vec<CosmosMsg>
 ) -> StdResult<()>{
-        let members = MEMBERS.load(deps.storage,&nft_address)?;
+        pay_out(store, &nft_address, royalty_portion, payout, messages)?;
+
+        //Transfer NFT to bidder
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: nft_address,
+                msg: to_json_binary(&Cw721ExecuteMsg::TransferNft { 
+                recipient: bidder,
+                token_id: token_id })?,
+                funds: vec![] }));
+
+        Ok(())
+}
+
+/// Proceeds of a sale paid out by `pay_out`
+struct Payout {
+    seller: String,
+    price: Asset,
+    token_address: Option<String>,
+    finder: Option<String>,
+}
+
+/// Pays the collection royalty, the finder fee and the seller out of the sale price
+fn pay_out(
+    store: &mut dyn Storage,
+    nft_address: &str,
+    royalty_portion: Decimal,
+    payout: Payout,
+    messages:& mut Vec<CosmosMsg>
+) -> StdResult<()>{
+        let Payout { seller, price: list_price, token_address, finder } = payout;
+        let members = MEMBERS.load(store,nft_address)?;
         let amount = list_price.amount;
+        let mut seller_amount = amount*(Decimal::one()-royalty_portion);
+
+        //Finder fee is paid out of the seller proceeds, a seller can not find their own sale
+        let finder_fee = COLLECTIONINFO.load(store, nft_address)?.finder_fee;
+        let finder_amount = seller_amount * finder_fee;
+        let finder = match finder {
+            Some(finder) if finder != seller && !finder_amount.is_zero() => {
+                seller_amount -= finder_amount;
+                FINDER_EARNINGS.update(store, (&finder, &list_price.denom), |earned| -> StdResult<_> {
+                    Ok(earned.unwrap_or_default() + finder_amount)
+                })?;
+                Some((finder, finder_amount))
+            }
+            _ => None,
+        };
+
         //Distribute money to the admins
         if token_address.is_none(){
             for user in members{
@@ -1716,6 +4431,13 @@ fn distribute_money(
                         }]
                 }))
             }
+
+            if let Some((finder, finder_amount)) = finder {
+                messages.push(CosmosMsg::Bank(BankMsg::Send {
+                    to_address: finder,
+                    amount: vec![Coin{ denom: list_price.denom.clone(), amount: finder_amount }]
+                }))
+            }
             
                 //Send money to asker
             messages.push(
@@ -1723,7 +4445,7 @@ fn distribute_money(
                     to_address: seller,
                     amount:vec![Coin{
                         denom:list_price.denom,
-                        amount:amount*(Decimal::one()-royalty_portion)
+                        amount:seller_amount
                     }]
                 })
             );
@@ -1738,24 +4460,26 @@ fn distribute_money(
                   })?, 
                   funds: vec![] }))
             }
+
+            if let Some((finder, finder_amount)) = finder {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute { 
+                  contract_addr: token_address.clone(), 
+                  msg: to_json_binary(&Cw20ExecuteMsg::Transfer { 
+                    recipient: finder, 
+                    amount: finder_amount
+                  })?, 
+                  funds: vec![] }))
+            }
             
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute { 
                   contract_addr: token_address, 
                   msg: to_binary(&Cw20ExecuteMsg::Transfer { 
                     recipient: seller, 
-                    amount: amount*(Decimal::one()-royalty_portion)
+                    amount: seller_amount
                   })?, 
                   funds: vec![] }));
                 //Send money to asker
        }
-            
-        //Transfer NFT to bidder
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: nft_address,
-                msg: to_binary(&Cw721ExecuteMsg::TransferNft { 
-                recipient: bidder.clone(),
-                token_id: token_id })?,
-                funds: vec![] }));
 
         Ok(())
 }
@@ -48,9 +48,51 @@ pub enum ContractError {
     #[error("This ask is expired")]
     AskExpired {},
 
+    #[error("This ask is reserved for another buyer")]
+    ReservedAsk {},
+
+    #[error("Counter-offer expired")]
+    CounterOfferExpired {},
+
+    #[error("Bid is below the reserve price")]
+    BidBelowReserve {},
+
+    #[error("Bid does not beat the highest bid by the minimum increment")]
+    BidIncrementTooLow {},
+
     #[error("Bid count is expired now")]
     BidCountExpired {},
 
     #[error("This bid is expired")]
     BidExpired {},
+
+    #[error("This hook is already registered")]
+    HookAlreadyRegistered {},
+
+    #[error("This hook is not registered")]
+    HookNotRegistered {},
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Trading rewards are not configured")]
+    NoRewardProgram {},
+
+    #[error("Reward token can not change while the reward pool is not empty")]
+    RewardPoolNotEmpty {},
+
+    #[error("Reward token can not change while accrued rewards are unclaimed")]
+    UnclaimedRewards {},
+
+    #[error("No rewards to claim")]
+    NothingToClaim {},
+
+    #[error("Finder can not be the buyer or the seller")]
+    InvalidFinder {},
+
+    #[error("No cleanup bounty is configured")]
+    NoCleanupBounty {},
+
+    #[error("Cleanup bounty denom can not change while the bounty pool is not empty")]
+    CleanupPoolNotEmpty {},
 }
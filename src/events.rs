@@ -0,0 +1,177 @@
+use cosmwasm_std::Event;
+use crate::state::{Ask, Asset, Bid, CollectionBid, CounterOffer, Offer, Cw1155Ask, Cw1155Bid};
+
+// Event types are emitted with the `wasm-` prefix, e.g. `wasm-ask-created`.
+// Every trading event uses the same keys: collection, token_id, seller, buyer, bidder, denom, amount, expires_at
+
+pub fn ask_created(ask: &Ask) -> Event {
+    let event = ask_event("ask-created", ask)
+        .add_attribute("expires_at", ask.expires_at.seconds().to_string());
+    match &ask.reserved_for {
+        Some(reserved_for) => event.add_attribute("reserved_for", reserved_for.clone()),
+        None => event,
+    }
+}
+
+pub fn ask_price_updated(ask: &Ask) -> Event {
+    ask_event("ask-price-updated", ask)
+}
+
+pub fn ask_updated(ask: &Ask) -> Event {
+    ask_event("ask-updated", ask)
+        .add_attribute("expires_at", ask.expires_at.seconds().to_string())
+}
+
+pub fn ask_removed(ask: &Ask) -> Event {
+    ask_event("ask-removed", ask)
+}
+
+pub fn bid_placed(bid: &Bid) -> Event {
+    bid_event("bid-placed", bid)
+        .add_attribute("expires_at", bid.expires_at.seconds().to_string())
+}
+
+pub fn bid_updated(bid: &Bid) -> Event {
+    bid_event("bid-updated", bid)
+        .add_attribute("expires_at", bid.expires_at.seconds().to_string())
+}
+
+pub fn bid_removed(bid: &Bid) -> Event {
+    bid_event("bid-removed", bid)
+}
+
+pub fn collection_bid_placed(bid: &CollectionBid) -> Event {
+    collection_bid_event("collection-bid-placed", bid)
+        .add_attribute("expires_at", bid.expires_at.seconds().to_string())
+}
+
+pub fn collection_bid_updated(bid: &CollectionBid) -> Event {
+    collection_bid_event("collection-bid-updated", bid)
+        .add_attribute("expires_at", bid.expires_at.seconds().to_string())
+}
+
+pub fn collection_bid_removed(bid: &CollectionBid) -> Event {
+    collection_bid_event("collection-bid-removed", bid)
+}
+
+pub fn counter_offer_made(offer: &CounterOffer) -> Event {
+    counter_offer_event("counter-offer-made", offer)
+        .add_attribute("expires_at", offer.expires_at.seconds().to_string())
+}
+
+pub fn counter_offer_removed(offer: &CounterOffer) -> Event {
+    counter_offer_event("counter-offer-removed", offer)
+}
+
+pub fn offer_placed(offer: &Offer) -> Event {
+    offer_event("offer-placed", offer)
+        .add_attribute("expires_at", offer.expires_at.seconds().to_string())
+}
+
+pub fn offer_removed(offer: &Offer) -> Event {
+    offer_event("offer-removed", offer)
+}
+
+pub fn cw1155_ask_created(ask: &Cw1155Ask) -> Event {
+    cw1155_ask_event("cw1155-ask-created", ask)
+        .add_attribute("expires_at", ask.expires_at.seconds().to_string())
+}
+
+pub fn cw1155_ask_removed(ask: &Cw1155Ask) -> Event {
+    cw1155_ask_event("cw1155-ask-removed", ask)
+}
+
+pub fn cw1155_bid_placed(bid: &Cw1155Bid) -> Event {
+    cw1155_bid_event("cw1155-bid-placed", bid)
+        .add_attribute("expires_at", bid.expires_at.seconds().to_string())
+}
+
+pub fn cw1155_bid_removed(bid: &Cw1155Bid) -> Event {
+    cw1155_bid_event("cw1155-bid-removed", bid)
+}
+
+/// `sale_type` is one of fixed_price, buy_now, auction, counter_offer, offer, collection_bid or cw1155
+pub fn sale(
+    collection: &str,
+    token_id: &str,
+    seller: &str,
+    buyer: &str,
+    price: &Asset,
+    sale_type: &str
+) -> Event {
+    Event::new("sale")
+        .add_attribute("collection", collection)
+        .add_attribute("token_id", token_id)
+        .add_attribute("seller", seller)
+        .add_attribute("buyer", buyer)
+        .add_attribute("denom", price.denom.clone())
+        .add_attribute("amount", price.amount.to_string())
+        .add_attribute("sale_type", sale_type)
+}
+
+fn ask_event(ty: &str, ask: &Ask) -> Event {
+    Event::new(ty)
+        .add_attribute("collection", ask.collection.clone())
+        .add_attribute("token_id", ask.token_id.clone())
+        .add_attribute("seller", ask.seller.clone())
+        .add_attribute("denom", ask.list_price.denom.clone())
+        .add_attribute("amount", ask.list_price.amount.to_string())
+}
+
+fn bid_event(ty: &str, bid: &Bid) -> Event {
+    Event::new(ty)
+        .add_attribute("collection", bid.collection.clone())
+        .add_attribute("token_id", bid.token_id.clone())
+        .add_attribute("seller", bid.seller.clone())
+        .add_attribute("bidder", bid.bidder.clone())
+        .add_attribute("denom", bid.list_price.denom.clone())
+        .add_attribute("amount", bid.list_price.amount.to_string())
+}
+
+fn counter_offer_event(ty: &str, offer: &CounterOffer) -> Event {
+    Event::new(ty)
+        .add_attribute("collection", offer.collection.clone())
+        .add_attribute("token_id", offer.token_id.clone())
+        .add_attribute("seller", offer.seller.clone())
+        .add_attribute("bidder", offer.bidder.clone())
+        .add_attribute("denom", offer.price.denom.clone())
+        .add_attribute("amount", offer.price.amount.to_string())
+}
+
+fn offer_event(ty: &str, offer: &Offer) -> Event {
+    Event::new(ty)
+        .add_attribute("collection", offer.collection.clone())
+        .add_attribute("token_id", offer.token_id.clone())
+        .add_attribute("bidder", offer.bidder.clone())
+        .add_attribute("denom", offer.price.denom.clone())
+        .add_attribute("amount", offer.price.amount.to_string())
+}
+
+// cw1155 events carry the unit price in denom/amount
+fn cw1155_ask_event(ty: &str, ask: &Cw1155Ask) -> Event {
+    Event::new(ty)
+        .add_attribute("collection", ask.collection.clone())
+        .add_attribute("token_id", ask.token_id.clone())
+        .add_attribute("seller", ask.seller.clone())
+        .add_attribute("denom", ask.unit_price.denom.clone())
+        .add_attribute("amount", ask.unit_price.amount.to_string())
+        .add_attribute("quantity", ask.quantity.to_string())
+}
+
+fn cw1155_bid_event(ty: &str, bid: &Cw1155Bid) -> Event {
+    Event::new(ty)
+        .add_attribute("collection", bid.collection.clone())
+        .add_attribute("token_id", bid.token_id.clone())
+        .add_attribute("bidder", bid.bidder.clone())
+        .add_attribute("denom", bid.unit_price.denom.clone())
+        .add_attribute("amount", bid.unit_price.amount.to_string())
+        .add_attribute("quantity", bid.quantity.to_string())
+}
+
+fn collection_bid_event(ty: &str, bid: &CollectionBid) -> Event {
+    Event::new(ty)
+        .add_attribute("collection", bid.collection.clone())
+        .add_attribute("bidder", bid.bidder.clone())
+        .add_attribute("denom", bid.list_price.denom.clone())
+        .add_attribute("amount", bid.list_price.amount.to_string())
+}
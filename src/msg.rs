@@ -2,9 +2,9 @@
 use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use crate::state::{Asset,UserInfo, TvlInfo, SaleInfo, SaleType, Ask, Bid, CollectionBid};
-use crate::package::QueryOfferingsResult;
-use cosmwasm_std::{Decimal, Timestamp, Uint128};
+use crate::state::{Asset,UserInfo, TvlInfo, SaleInfo, SaleType, Ask, Bid, CollectionBid, Candle, CandleInterval, Activity, HookType, RewardConfig, CounterOffer, Offer, NftStandard, Cw1155Ask, Cw1155Bid, Ics721Origin};
+use crate::package::{QueryOfferingsResult, Cw1155ReceiveMsg};
+use cosmwasm_std::{to_json_binary, CosmosMsg, Decimal, Order, StdResult, Timestamp, Uint128, WasmMsg};
 use cw721::Cw721ReceiveMsg;
 
 
@@ -28,13 +28,26 @@ pub enum ExecuteMsg {
         list_price: Asset,
         token_address: Option<String>
     },
-    Receive(Cw20ReceiveMsg), 
+    /// Changes any of the listing settings at once. Open bids in another denom than the new list price are refunded
+    UpdateAsk(UpdateAskMsg),
+    Receive(ReceiveMsg), 
     SetBidCoin{
         nft_address:String, 
         expire: Timestamp, 
         sale_type: SaleType, 
         token_id: Option<String>, 
-        list_price:Asset
+        list_price:Asset,
+        /// Referrer that routed the buyer here, paid the collection finder fee
+        finder: Option<String>,
+        /// Receiver on the origin chain. ICS-721 voucher NFTs bought outright are sent back there through the bridge
+        origin_receiver: Option<String>
+    },
+    /// Tops up the sender's coin bid with the sent funds and can change its expiry.
+    /// Without `token_id` it updates the sender's collection bid
+    UpdateBid{
+        nft_address: String,
+        token_id: Option<String>,
+        expire: Option<Timestamp>
     },
     RemoveBid{
         nft_address: String,
@@ -48,11 +61,42 @@ pub enum ExecuteMsg {
     RemoveCollectionBid { 
         nft_address: String 
     },
+    /// Seller of a listed token sells it into a collection bid
     AcceptCollectionBid {
         nft_address: String,
         token_id: String,
         bidder: String,
     },
+    /// Seller proposes a price, in the bid denom, to one bidder on a listed token
+    MakeCounterOffer(MakeCounterOfferMsg),
+    RemoveCounterOffer {
+        nft_address: String,
+        token_id: String,
+        bidder: String,
+    },
+    /// Bidder buys at the counter-offer price by sending the difference to their coin bid
+    AcceptCounterOffer {
+        nft_address: String,
+        token_id: String,
+    },
+    /// Withdraws the sender's offer and refunds it
+    RemoveOffer {
+        nft_address: String,
+        token_id: String,
+    },
+    /// Buys `quantity` units of a cw1155 listing, paying `unit_price * quantity` in coins
+    BuyCw1155(BuyCw1155Msg),
+    /// Bids for `quantity` units of a cw1155 token, escrowing `unit_price * quantity`. Replaces the sender's previous bid
+    SetCw1155Bid(SetCw1155BidMsg),
+    RemoveCw1155Bid {
+        nft_address: String,
+        token_id: String,
+    },
+    /// Returns the unsold units of the sender's cw1155 listing
+    WithdrawCw1155 {
+        nft_address: String,
+        token_id: String,
+    },
     /// Priviledged operation to change the active state of an ask when an NFT is transferred
     ChangeOwner{
         address: String
@@ -73,6 +117,31 @@ pub enum ExecuteMsg {
         members:Vec<UserInfo>,
         nft_address:String
     },
+    /// Share of the seller proceeds paid to the finder of a purchase
+    SetFinderFee{
+        nft_address:String,
+        finder_fee:Decimal
+    },
+    /// Minimum raise over the highest auction bid for a collection
+    SetMinBidIncrement{
+        nft_address:String,
+        min_bid_increment:Decimal
+    },
+    /// Minimum auction bid for a collection as a share of the list price, used when the listing has no reserve price
+    SetMinBidPortion{
+        nft_address:String,
+        min_bid_portion:Decimal
+    },
+    /// Marks a registered collection as a voucher collection of the ICS-721 `bridge`, `None` unmarks it
+    SetIcs721Bridge{
+        nft_address:String,
+        bridge:Option<String>
+    },
+    /// Token interface of a registered collection, cw721 unless set
+    SetNftStandard{
+        nft_address:String,
+        standard:NftStandard
+    },
     FixNft{
         address:String,
         token_id:String
@@ -104,9 +173,191 @@ pub enum ExecuteMsg {
     ChangeAdmin{
         address:String
     },
+    /// Moves up to `limit` sale history entries from the old (collection, token_id, time) layout
+    /// into the sequence numbered one. Call repeatedly after migrating until nothing is left.
+    /// Entries get their `seq` in (collection, token_id, time) order, not in the order they sold,
+    /// so across tokens only `(time, seq)` orders migrated sales by time
+    MigrateSaleHistory{
+        limit: Option<u32>
+    },
+    /// Re-saves up to `limit` asks, bids and collection bids so orders placed before the expiry
+    /// indexes existed show up in them. Call repeatedly after migrating until it reports `done`
+    BackfillExpiryIndexes{
+        limit: Option<u32>
+    },
+    /// Registers a contract to be called with `MarketplaceHookMsg` on the given events
+    AddHook{
+        hook_type: HookType,
+        address: String
+    },
+    RemoveHook{
+        hook_type: HookType,
+        address: String
+    },
+    /// Sets the cw20 reward token and the epoch length of the trading reward program
+    SetRewardConfig{
+        token_address: String,
+        epoch_seconds: u64
+    },
+    /// Reward tokens paid to both buyer and seller per unit of volume. A zero rate removes it
+    SetRewardRate{
+        collection: String,
+        denom: String,
+        rate: Decimal
+    },
+    /// Pays out rewards of epochs that have ended
+    ClaimRewards{},
+    /// Removes expired asks, bids and collection bids and returns their NFTs and funds. Anyone can call it
+    CleanupExpired{
+        limit: Option<u32>
+    },
+    /// Most coins paid to the caller of `CleanupExpired` per removed order, `None` turns the bounty off
+    SetCleanupBounty{
+        bounty: Option<Asset>
+    },
+    /// Adds the sent coins to the cleanup bounty pool
+    FundCleanupBounty{},
     
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
+/// cw20 and cw1155 contracts both call `receive`, the payload fields tell them apart
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum ReceiveMsg {
+    Cw20(Cw20ReceiveMsg),
+    Cw1155(Cw1155ReceiveMsg),
+}
+
+/// cw1155 `SendFrom` payloads
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw1155HookMsg {
+    /// Lists the sent units at `unit_price`. Adds to the sender's listing of the token, which must have the same price, asset and expiry
+    Sell{
+        unit_price: Asset,
+        token_address: Option<String>,
+        expire: Timestamp,
+    },
+    /// Sells the sent units into the bidder's cw1155 bid
+    AcceptBid{
+        bidder: String,
+    },
+}
+
+/// Cw721 `SendNft` payloads other than `SellNft`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NftHookMsg {
+    /// Sells the sent token to the bidder's offer
+    AcceptOffer{
+        bidder: String,
+    },
+    /// Sells the sent token into the bidder's collection bid
+    AcceptCollectionBid{
+        bidder: String,
+    },
+}
+
+/// Cw20 `Send` payloads other than `BuyNft`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenHookMsg {
+    /// Adds the sent reward tokens to the trading reward pool
+    FundRewards{},
+    /// Tops up the sender's cw20 bid with the sent tokens and can change its expiry.
+    /// Without `token_id` it updates the sender's collection bid
+    UpdateBid{
+        nft_address: String,
+        token_id: Option<String>,
+        expire: Option<Timestamp>
+    },
+    /// Buys at the counter-offer price, the sent tokens cover the difference to the sender's cw20 bid
+    AcceptCounterOffer{
+        nft_address: String,
+        token_id: String,
+    },
+    /// Buys `quantity` units of a cw1155 listing priced in the sent token
+    BuyCw1155(BuyCw1155Msg),
+    /// Bids for `quantity` units of a cw1155 token, the sent amount is the total price
+    SetCw1155Bid{
+        nft_address: String,
+        token_id: String,
+        quantity: Uint128,
+        expire: Timestamp,
+    },
+}
+
+/// Message sent to registered hook contracts. Hook failures do not revert the trade
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketplaceHookMsg {
+    SaleHook(SaleHookMsg),
+    AskHook(AskHookMsg),
+    BidHook(BidHookMsg),
+    CollectionBidHook(CollectionBidHookMsg),
+    OfferHook(OfferHookMsg),
+    Cw1155AskHook(Cw1155AskHookMsg),
+    Cw1155BidHook(Cw1155BidHookMsg),
+}
+
+impl MarketplaceHookMsg {
+    /// Wraps the hook message in a `WasmMsg::Execute` to the hook contract
+    pub fn into_cosmos_msg(self, contract_addr: String) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr,
+            msg: to_json_binary(&self)?,
+            funds: vec![],
+        }
+        .into())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SaleHookMsg {
+    pub collection: String,
+    pub token_id: String,
+    pub seller: String,
+    pub buyer: String,
+    pub price: Asset,
+    pub time: u64,
+    /// Units sold in a cw1155 sale, where `price` is the unit price
+    #[serde(default)]
+    pub quantity: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AskHookMsg {
+    pub ask: Ask,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidHookMsg {
+    pub bid: Bid,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectionBidHookMsg {
+    pub collection_bid: CollectionBid,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OfferHookMsg {
+    pub offer: Offer,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw1155AskHookMsg {
+    pub ask: Cw1155Ask,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw1155BidHookMsg {
+    pub bid: Cw1155Bid,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
@@ -208,11 +459,11 @@ pub enum QueryMsg {
         start_after: Option<SaleHistoryOffset>,
         limit: Option<u32>
     },
-    //start after is time
+    //start after is (time, seq), or the time alone as sent before sequence numbers
     SaleHistoryByTokenId{
         collection: String,
         token_id: String,
-        start_after: Option<u64>,
+        start_after: Option<SaleHistoryTokenOffset>,
         limit: Option<u32>
     },
     //start after is denom
@@ -240,6 +491,231 @@ pub enum QueryMsg {
         buyer: String,
         start_after: Option<SaleHistoryOffsetByUser>,
         limit: Option<u32>
+    },
+    /// Get all asks for a collection in a denom, sorted by price from the floor up
+    /// Return type: `AsksResponse`
+    AsksSortedByPrice {
+        collection: String,
+        denom: String,
+        start_after: Option<AskPriceOffset>,
+        limit: Option<u32>,
+    },
+    /// Get all asks for a collection in a denom, sorted by price from the top down
+    /// Return type: `AsksResponse`
+    ReverseAsksSortedByPrice {
+        collection: String,
+        denom: String,
+        start_before: Option<AskPriceOffset>,
+        limit: Option<u32>,
+    },
+    /// Get the highest unexpired bid for a specific NFT in a denom
+    /// Return type: `BidResponse`
+    TopBid {
+        collection: String,
+        token_id: String,
+        denom: String,
+    },
+    /// Get the highest unexpired collection bid for a collection in a denom
+    /// Return type: `CollectionBidResponse`
+    HighestCollectionBid {
+        collection: String,
+        denom: String,
+    },
+    /// Get sale statistics and the current floor for a collection in a denom
+    /// Return type: `CollectionStatsResponse`
+    CollectionStats {
+        collection: String,
+        denom: String,
+    },
+    /// Get OHLC candles for a collection in a denom, oldest first
+    /// start_time is inclusive and end_time exclusive, page by passing the last start_time + 1
+    /// Return type: `CandlesResponse`
+    Candles {
+        collection: String,
+        denom: String,
+        interval: CandleInterval,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Get sales of a collection between start_time (inclusive) and end_time (exclusive)
+    /// Return type: `SaleHistroyResponse`
+    SaleHistoryByCollectionInRange {
+        collection: String,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+        denom: Option<String>,
+        start_after: Option<SaleHistoryTimeOffset>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    /// Get sales made by a seller between start_time (inclusive) and end_time (exclusive)
+    /// Return type: `SaleHistroyResponse`
+    SaleHistoryBySellerInRange {
+        seller: String,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+        denom: Option<String>,
+        start_after: Option<SaleHistoryTimeOffset>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    /// Get purchases made by a buyer between start_time (inclusive) and end_time (exclusive)
+    /// Return type: `SaleHistroyResponse`
+    SaleHistoryByBuyerInRange {
+        buyer: String,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+        denom: Option<String>,
+        start_after: Option<SaleHistoryTimeOffset>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    /// Get the activity log of a collection, newest first. start_after is an activity id
+    /// Return type: `ActivitiesResponse`
+    ActivitiesByCollection {
+        collection: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Get the activity log of a single token, newest first
+    /// Return type: `ActivitiesResponse`
+    ActivitiesByToken {
+        collection: String,
+        token_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Get activities where the user is the actor or the counterparty, newest first
+    /// Return type: `ActivitiesResponse`
+    ActivitiesByUser {
+        user: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Return type: `HooksResponse`
+    Hooks {
+        hook_type: HookType,
+    },
+    /// Return type: `RewardProgramResponse`
+    RewardProgram {},
+    /// Return type: `CleanupBountyResponse`
+    CleanupBounty {},
+    /// Return type: `Decimal`
+    RewardRate {
+        collection: String,
+        denom: String,
+    },
+    /// Return type: `PendingRewardsResponse`
+    PendingRewards {
+        address: String,
+    },
+    /// Finder fees earned per denom
+    /// Return type: `FinderEarningsResponse`
+    FinderEarnings {
+        finder: String,
+    },
+    /// Asks across all collections expiring before `expires_before`, soonest first, so expired asks come first
+    /// Return type: `AsksResponse`
+    AsksByExpiry {
+        expires_before: Timestamp,
+        start_after: Option<AskExpiryOffset>,
+        limit: Option<u32>,
+    },
+    /// Bids across all collections expiring before `expires_before`, soonest first
+    /// Return type: `BidsResponse`
+    BidsByExpiry {
+        expires_before: Timestamp,
+        start_after: Option<BidExpiryOffset>,
+        limit: Option<u32>,
+    },
+    /// Collection bids across all collections expiring before `expires_before`, soonest first
+    /// Return type: `CollectionBidsResponse`
+    CollectionBidsByExpiry {
+        expires_before: Timestamp,
+        start_after: Option<CollectionBidExpiryOffset>,
+        limit: Option<u32>,
+    },
+    /// Return type: `CounterOfferResponse`
+    CounterOffer {
+        collection: String,
+        token_id: String,
+        bidder: String,
+    },
+    /// Counter-offers received by a bidder
+    /// Return type: `CounterOffersResponse`
+    CounterOffersByBidder {
+        bidder: String,
+        start_after: Option<CollectionOffset>,
+        limit: Option<u32>,
+    },
+    /// Counter-offers made by a seller
+    /// Return type: `CounterOffersResponse`
+    CounterOffersBySeller {
+        seller: String,
+        start_after: Option<CollectionOffsetBid>,
+        limit: Option<u32>,
+    },
+    /// Return type: `OfferResponse`
+    Offer {
+        collection: String,
+        token_id: String,
+        bidder: String,
+    },
+    /// Offers on a token, paginated by bidder
+    /// Return type: `OffersResponse`
+    OffersByToken {
+        collection: String,
+        token_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Return type: `OffersResponse`
+    OffersByBidder {
+        bidder: String,
+        start_after: Option<CollectionOffset>,
+        limit: Option<u32>,
+    },
+    /// cw1155 listings of a token, paginated by seller
+    /// Return type: `Cw1155AsksResponse`
+    Cw1155Asks {
+        collection: String,
+        token_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// cw1155 bids on a token, paginated by bidder
+    /// Return type: `Cw1155BidsResponse`
+    Cw1155Bids {
+        collection: String,
+        token_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    }
+}
+
+/// Optional listing settings that `UpdateAsk` can clear
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AskSetting {
+    BuyNowPrice,
+    ReservePrice,
+    MinBidIncrement
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Ascending,
+    Descending
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Order {
+        match order {
+            SortOrder::Ascending => Order::Ascending,
+            SortOrder::Descending => Order::Descending,
+        }
     }
 }
 
@@ -259,6 +735,31 @@ pub struct CollectionOffsetBid {
     pub bidder: String
 }
 
+/// Offset for ask pagination sorted by expiration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AskExpiryOffset {
+    pub expires_at: Timestamp,
+    pub collection: String,
+    pub token_id: String,
+}
+
+/// Offset for bid pagination sorted by expiration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidExpiryOffset {
+    pub expires_at: Timestamp,
+    pub collection: String,
+    pub token_id: String,
+    pub bidder: String,
+}
+
+/// Offset for collection bid pagination sorted by expiration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectionBidExpiryOffset {
+    pub expires_at: Timestamp,
+    pub collection: String,
+    pub bidder: String,
+}
+
 /// Offset for collection bid pagination
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CollectionBidOffset {
@@ -279,11 +780,21 @@ impl CollectionBidOffset {
 
 
 
+/// Offset for price sorted ask pagination
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AskPriceOffset {
+    pub price: Uint128,
+    pub token_id: String,
+}
+
 /// Salehistory offset for the pagination sale histroy by collection
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct SaleHistoryOffset {
     pub token_id: String,
-    pub time: u64
+    pub time: u64,
+    /// Left out by clients from before sequence numbers, which skips every sale of the token at `time`
+    #[serde(default)]
+    pub seq: Option<u64>
 }
 
 /// Salehistory offset for the pagination sale histroy by collection
@@ -291,16 +802,95 @@ pub struct SaleHistoryOffset {
 pub struct SaleHistoryOffsetByUser {
     pub collection:String,
     pub token_id: String,
-    pub time: u64
+    pub time: u64,
+    /// Left out by clients from before sequence numbers, which skips every sale of the token at `time`
+    #[serde(default)]
+    pub seq: Option<u64>
+}
+
+/// Salehistory offset for the pagination sale histroy by token_id
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SaleHistoryOffsetByToken {
+    pub time: u64,
+    pub seq: u64
+}
+
+/// Token sale history offset, either a sale or a bare time that skips every sale at that time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum SaleHistoryTokenOffset {
+    Time(u64),
+    Sale(SaleHistoryOffsetByToken)
+}
+
+/// Salehistory offset for the time range pagination, the last sale of the previous page
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SaleHistoryTimeOffset {
+    pub collection: String,
+    pub token_id: String,
+    pub time: u64,
+    pub seq: u64
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct SellNft {
     pub list_price: Asset,
     pub expire: Timestamp,
-    pub token_address: Option<String>
+    pub token_address: Option<String>,
+    /// Sell right away to the best collection bid at or above the list price
+    pub instant_sell: Option<bool>,
+    /// Restricts the listing to a single buyer, e.g. to settle an OTC deal
+    pub reserved_for: Option<String>,
+    /// Auction bids at or above this amount, in the list price denom, settle immediately
+    pub buy_now_price: Option<Uint128>,
+    /// Minimum auction bid, in the list price denom
+    pub reserve_price: Option<Uint128>,
+    /// Minimum raise over the highest bid, overriding the collection setting
+    pub min_bid_increment: Option<Decimal>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UpdateAskMsg {
+    pub nft_address: String,
+    pub token_id: String,
+    /// A new denom drops the buy now price, reserve price and bid increment unless they are set again
+    pub list_price: Option<Asset>,
+    /// Required when the new list price is a cw20 token
+    pub token_address: Option<String>,
+    pub expire: Option<Timestamp>,
+    pub buy_now_price: Option<Uint128>,
+    pub reserve_price: Option<Uint128>,
+    pub min_bid_increment: Option<Decimal>,
+    /// Settings to remove from the listing, they can not be set in the same message
+    #[serde(default)]
+    pub clear: Vec<AskSetting>
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MakeCounterOfferMsg {
+    pub nft_address: String,
+    pub token_id: String,
+    pub bidder: String,
+    pub price: Uint128,
+    pub expire: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BuyCw1155Msg {
+    pub nft_address: String,
+    pub token_id: String,
+    pub seller: String,
+    pub quantity: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SetCw1155BidMsg {
+    pub nft_address: String,
+    pub token_id: String,
+    pub quantity: Uint128,
+    pub unit_price: Asset,
+    pub expire: Timestamp,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -308,18 +898,35 @@ pub struct BuyNft {
     pub nft_address : String,
     pub expire: Timestamp,
     pub sale_type: SaleType,
-    pub token_id: Option<String>
+    pub token_id: Option<String>,
+    /// Referrer that routed the buyer here, paid the collection finder fee
+    pub finder: Option<String>,
+    /// Receiver on the origin chain. ICS-721 voucher NFTs bought outright are sent back there through the bridge
+    #[serde(default)]
+    pub origin_receiver: Option<String>
 }
 
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct AskResponse {
     pub ask: Option<Ask>,
+    /// Origin chain of the NFT if its collection is an ICS-721 voucher collection
+    #[serde(default)]
+    pub origin: Option<Ics721Origin>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct AsksResponse {
     pub asks: Vec<Ask>,
+    /// Origin chains of the ICS-721 voucher collections listed in `asks`
+    #[serde(default)]
+    pub origins: Vec<CollectionOrigin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectionOrigin {
+    pub collection: String,
+    pub origin: Ics721Origin,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -363,4 +970,91 @@ pub struct CollectionBidResponse {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CollectionBidsResponse {
     pub bids: Vec<CollectionBid>,
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CounterOfferResponse {
+    pub counter_offer: Option<CounterOffer>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CounterOffersResponse {
+    pub counter_offers: Vec<CounterOffer>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OfferResponse {
+    pub offer: Option<Offer>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OffersResponse {
+    pub offers: Vec<Offer>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw1155AsksResponse {
+    pub asks: Vec<Cw1155Ask>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw1155BidsResponse {
+    pub bids: Vec<Cw1155Bid>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectionStatsResponse {
+    pub collection: String,
+    pub denom: String,
+    pub sale_count: u64,
+    pub last_sale_price: Option<Uint128>,
+    pub last_sale_time: Option<u64>,
+    pub all_time_high: Option<Uint128>,
+    pub total_volume: Uint128,
+    /// Rolling windows are counted in hour buckets, so each one reaches back up to an hour less than its length
+    pub volume_24h: Uint128,
+    pub volume_7d: Uint128,
+    pub volume_30d: Uint128,
+    /// Lowest unexpired ask in this denom
+    pub floor_price: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CandlesResponse {
+    pub candles: Vec<Candle>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ActivitiesResponse {
+    pub activities: Vec<Activity>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HooksResponse {
+    pub hooks: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardProgramResponse {
+    pub config: Option<RewardConfig>,
+    pub pool: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CleanupBountyResponse {
+    pub bounty: Option<Asset>,
+    pub pool: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FinderEarningsResponse {
+    pub earnings: Vec<Asset>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRewardsResponse {
+    /// Rewards of ended epochs
+    pub claimable: Uint128,
+    /// Rewards of the current epoch
+    pub accruing: Uint128,
+}
@@ -1,3 +1,4 @@
+use cosmwasm_std::{Binary, IbcTimeout, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use crate::state::Asset;
@@ -15,4 +16,44 @@ pub struct OfferingsResponse {
     pub offerings: Vec<QueryOfferingsResult>,
 }
 
+/// Hook a cw1155 contract calls on the recipient of `SendFrom`/`Send`. `from` is empty for mints
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw1155ReceiveMsg {
+    pub operator: String,
+    pub from: Option<String>,
+    pub token_id: String,
+    pub amount: Uint128,
+    pub msg: Binary,
+}
+
+/// The part of the cw1155 execute interface the marketplace calls
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw1155ExecuteMsg {
+    SendFrom {
+        from: String,
+        to: String,
+        token_id: String,
+        value: Uint128,
+        msg: Option<Binary>,
+    },
+}
+
+/// The part of the ICS-721 bridge query interface the marketplace calls
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Ics721QueryMsg {
+    /// Class id of a voucher collection the bridge instantiated, `None` for other contracts
+    ClassId { contract: String },
+}
+
+/// `SendNft` payload that makes the ICS-721 bridge transfer the NFT over `channel_id`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Ics721OutgoingMsg {
+    pub receiver: String,
+    pub channel_id: String,
+    pub timeout: IbcTimeout,
+    pub memo: Option<String>,
+}
+
 // THIS FILE SHOULD BE EXTRACTED TO ITS OWN PACKAGE PROJECT LIKE CW20 OR CW721
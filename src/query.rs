@@ -1,11 +1,15 @@
 use crate::msg::{
-    AskCountResponse,  AskResponse, AsksResponse,  BidResponse, BidsResponse,CollectionOffset, QueryMsg, CollectionOffsetBid, SaleHistoryOffset, SaleHistroyResponse, TvlResponse, TvlIndividualResponse, CollectionBidOffset, CollectionBidResponse, CollectionBidsResponse, SaleHistoryOffsetByUser
+    AskCountResponse,  AskResponse, AsksResponse,  BidResponse, BidsResponse,CollectionOffset, QueryMsg, CollectionOffsetBid, SaleHistoryOffset, SaleHistroyResponse, TvlResponse, TvlIndividualResponse, CollectionBidOffset, CollectionBidResponse, CollectionBidsResponse, SaleHistoryOffsetByUser, AskPriceOffset, CollectionStatsResponse, CandlesResponse,
+    SaleHistoryTimeOffset, SortOrder, SaleHistoryTokenOffset, ActivitiesResponse, HooksResponse, RewardProgramResponse, PendingRewardsResponse, FinderEarningsResponse,
+    CleanupBountyResponse, AskExpiryOffset, BidExpiryOffset, CollectionBidExpiryOffset, CounterOfferResponse, CounterOffersResponse, OfferResponse, OffersResponse, Cw1155AsksResponse, Cw1155BidsResponse, CollectionOrigin
 };
 use crate::state::{
-    ask_key, asks, bid_key, bids,  BidKey, State, CONFIG, CollectionInfo, COLLECTIONINFO, MEMBERS, UserInfo, sale_history_key, sale_history, tvl,collection_bid_key,collection_bids
+    ask_key, asks, bid_key, bids,  BidKey, State, CONFIG, CollectionInfo, COLLECTIONINFO, MEMBERS, UserInfo, sale_history_key, sale_history, tvl,collection_bid_key,collection_bids,
+    COLLECTION_STATS, VOLUME_BUCKETS, VOLUME_BUCKET_SECONDS, DAY_SECONDS, CandleInterval, SaleInfo, SaleHistoryKey, activities, USER_ACTIVITIES, HookType, REWARD_CONFIG, REWARD_POOL, REWARD_RATES, EPOCH_REWARDS, FINDER_EARNINGS, CLEANUP_BOUNTY, CLEANUP_POOL, Asset,
+    counter_offers, offers, cw1155_asks, cw1155_bids, Ask
 };
-use cosmwasm_std::{entry_point, to_binary, Addr, Binary, Deps, Env, Order, StdResult, Uint128};
-use cw_storage_plus::{Bound, PrefixBound};
+use cosmwasm_std::{entry_point, to_binary, to_json_binary, Addr, Binary, Decimal, Deps, Env, Order, StdResult, Timestamp, Uint128};
+use cw_storage_plus::{Bound, MultiIndex, PrefixBound};
 
 // Query limits
 const DEFAULT_QUERY_LIMIT: u32 = 10;
@@ -13,7 +17,7 @@ const MAX_QUERY_LIMIT: u32 = 30;
 
 
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     let api = deps.api;
 
     match msg {
@@ -138,6 +142,64 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after,
             limit,
         )?),
+        QueryMsg::AsksByExpiry {
+            expires_before,
+            start_after,
+            limit,
+        } => to_json_binary(&query_asks_by_expiry(deps, expires_before, start_after, limit)?),
+        QueryMsg::BidsByExpiry {
+            expires_before,
+            start_after,
+            limit,
+        } => to_json_binary(&query_bids_by_expiry(deps, expires_before, start_after, limit)?),
+        QueryMsg::CollectionBidsByExpiry {
+            expires_before,
+            start_after,
+            limit,
+        } => to_json_binary(&query_collection_bids_by_expiry(deps, expires_before, start_after, limit)?),
+        QueryMsg::CounterOffer {
+            collection,
+            token_id,
+            bidder,
+        } => to_json_binary(&query_counter_offer(deps, collection, token_id, bidder)?),
+        QueryMsg::CounterOffersByBidder {
+            bidder,
+            start_after,
+            limit,
+        } => to_json_binary(&query_counter_offers_by_bidder(deps, bidder, start_after, limit)?),
+        QueryMsg::CounterOffersBySeller {
+            seller,
+            start_after,
+            limit,
+        } => to_json_binary(&query_counter_offers_by_seller(deps, seller, start_after, limit)?),
+        QueryMsg::Offer {
+            collection,
+            token_id,
+            bidder,
+        } => to_json_binary(&query_offer(deps, collection, token_id, bidder)?),
+        QueryMsg::OffersByToken {
+            collection,
+            token_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query_offers_by_token(deps, collection, token_id, start_after, limit)?),
+        QueryMsg::OffersByBidder {
+            bidder,
+            start_after,
+            limit,
+        } => to_json_binary(&query_offers_by_bidder(deps, bidder, start_after, limit)?),
+        QueryMsg::Cw1155Asks {
+            collection,
+            token_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query_cw1155_asks(deps, collection, token_id, start_after, limit)?),
+        QueryMsg::Cw1155Bids {
+            collection,
+            token_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query_cw1155_bids(deps, collection, token_id, start_after, limit)?),
         QueryMsg::SaleHistoryByCollection {
            collection,
            start_after,
@@ -212,7 +274,165 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
           collection,
           start_after,
           limit
-        )?)  
+        )?),
+        QueryMsg::AsksSortedByPrice {
+            collection,
+            denom,
+            start_after,
+            limit,
+        } => to_json_binary(&query_asks_sorted_by_price(
+            deps,
+            collection,
+            denom,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ReverseAsksSortedByPrice {
+            collection,
+            denom,
+            start_before,
+            limit,
+        } => to_json_binary(&reverse_query_asks_sorted_by_price(
+            deps,
+            collection,
+            denom,
+            start_before,
+            limit,
+        )?),
+        QueryMsg::TopBid {
+            collection,
+            token_id,
+            denom,
+        } => to_json_binary(&query_top_bid(
+            deps,
+            env,
+            collection,
+            token_id,
+            denom,
+        )?),
+        QueryMsg::HighestCollectionBid {
+            collection,
+            denom,
+        } => to_json_binary(&query_highest_collection_bid(
+            deps,
+            env,
+            collection,
+            denom,
+        )?),
+        QueryMsg::CollectionStats {
+            collection,
+            denom,
+        } => to_json_binary(&query_collection_stats(
+            deps,
+            env,
+            collection,
+            denom,
+        )?),
+        QueryMsg::Candles {
+            collection,
+            denom,
+            interval,
+            start_time,
+            end_time,
+            limit,
+        } => to_json_binary(&query_candles(
+            deps,
+            collection,
+            denom,
+            interval,
+            start_time,
+            end_time,
+            limit,
+        )?),
+        QueryMsg::SaleHistoryByCollectionInRange {
+            collection,
+            start_time,
+            end_time,
+            denom,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query_sale_history_in_range(
+            deps,
+            sale_history().idx.collection_time,
+            sale_history().idx.collection_denom_time,
+            collection,
+            SaleHistoryRange { start_time, end_time, denom, start_after },
+            limit,
+            order,
+        )?),
+        QueryMsg::SaleHistoryBySellerInRange {
+            seller,
+            start_time,
+            end_time,
+            denom,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query_sale_history_in_range(
+            deps,
+            sale_history().idx.seller_time,
+            sale_history().idx.seller_denom_time,
+            seller,
+            SaleHistoryRange { start_time, end_time, denom, start_after },
+            limit,
+            order,
+        )?),
+        QueryMsg::SaleHistoryByBuyerInRange {
+            buyer,
+            start_time,
+            end_time,
+            denom,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query_sale_history_in_range(
+            deps,
+            sale_history().idx.buyer_time,
+            sale_history().idx.buyer_denom_time,
+            buyer,
+            SaleHistoryRange { start_time, end_time, denom, start_after },
+            limit,
+            order,
+        )?),
+        QueryMsg::ActivitiesByCollection {
+            collection,
+            start_after,
+            limit,
+        } => to_json_binary(&query_activities_by_collection(
+            deps,
+            collection,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ActivitiesByToken {
+            collection,
+            token_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query_activities_by_token(
+            deps,
+            collection,
+            token_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ActivitiesByUser {
+            user,
+            start_after,
+            limit,
+        } => to_json_binary(&query_activities_by_user(
+            deps,
+            user,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Hooks { hook_type } => to_json_binary(&query_hooks(deps, hook_type)?),
+        QueryMsg::RewardProgram {} => to_json_binary(&query_reward_program(deps)?),
+        QueryMsg::CleanupBounty {} => to_json_binary(&query_cleanup_bounty(deps)?),
+        QueryMsg::RewardRate { collection, denom } => to_json_binary(&query_reward_rate(deps, collection, denom)?),
+        QueryMsg::PendingRewards { address } => to_json_binary(&query_pending_rewards(deps, env, address)?),
+        QueryMsg::FinderEarnings { finder } => to_json_binary(&query_finder_earnings(deps, finder)?),
     }
 }
 
@@ -237,8 +457,27 @@ pub fn query_get_members(deps:Deps,address:String) -> StdResult<Vec<UserInfo>>{
 
 pub fn query_ask(deps: Deps, collection: String, token_id: String) -> StdResult<AskResponse> {
     let ask = asks().may_load(deps.storage, ask_key(&collection, &token_id))?;
+    let origin = match &ask {
+        Some(_) => COLLECTIONINFO.may_load(deps.storage, &collection)?.and_then(|info| info.ics721),
+        None => None,
+    };
+
+    Ok(AskResponse { ask, origin })
+}
+
+/// Attaches the origin chains of the ICS-721 voucher collections in `asks`
+fn asks_response(deps: Deps, asks: Vec<Ask>) -> StdResult<AsksResponse> {
+    let mut origins: Vec<CollectionOrigin> = Vec::new();
+    for ask in &asks {
+        if origins.iter().any(|origin| origin.collection == ask.collection) {
+            continue;
+        }
+        if let Some(origin) = COLLECTIONINFO.may_load(deps.storage, &ask.collection)?.and_then(|info| info.ics721) {
+            origins.push(CollectionOrigin { collection: ask.collection.clone(), origin });
+        }
+    }
 
-    Ok(AskResponse { ask })
+    Ok(AsksResponse { asks, origins })
 }
 
 
@@ -267,7 +506,7 @@ pub fn query_asks(
         .map(|res| res.map(|item| item.1))
         .collect::<StdResult<Vec<_>>>()?;
 
-    Ok(AsksResponse { asks })
+    asks_response(deps, asks)
 }
 
 pub fn reverse_query_asks(
@@ -295,7 +534,7 @@ pub fn reverse_query_asks(
         .map(|res| res.map(|item| item.1))
         .collect::<StdResult<Vec<_>>>()?;
 
-    Ok(AsksResponse { asks })
+    asks_response(deps, asks)
 }
 
 pub fn query_ask_count(deps: Deps, collection: String) -> StdResult<AskCountResponse> {
@@ -334,7 +573,63 @@ pub fn query_asks_by_seller(
         .map(|res| res.map(|item| item.1))
         .collect::<StdResult<Vec<_>>>()?;
 
-    Ok(AsksResponse { asks })
+    asks_response(deps, asks)
+}
+
+pub fn query_asks_sorted_by_price(
+    deps: Deps,
+    collection: String,
+    denom: String,
+    start_after: Option<AskPriceOffset>,
+    limit: Option<u32>,
+) -> StdResult<AsksResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+    let start = start_after.map(|offset| {
+        Bound::exclusive((
+            offset.price.u128(),
+            ask_key(&collection, &offset.token_id),
+        ))
+    });
+
+    let asks = asks()
+        .idx
+        .collection_price
+        .sub_prefix((collection.clone(), denom))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| res.map(|item| item.1))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    asks_response(deps, asks)
+}
+
+pub fn reverse_query_asks_sorted_by_price(
+    deps: Deps,
+    collection: String,
+    denom: String,
+    start_before: Option<AskPriceOffset>,
+    limit: Option<u32>,
+) -> StdResult<AsksResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+    let end = start_before.map(|offset| {
+        Bound::exclusive((
+            offset.price.u128(),
+            ask_key(&collection, &offset.token_id),
+        ))
+    });
+
+    let asks = asks()
+        .idx
+        .collection_price
+        .sub_prefix((collection.clone(), denom))
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|res| res.map(|item| item.1))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    asks_response(deps, asks)
 }
 
 
@@ -403,6 +698,28 @@ pub fn query_bids(
     Ok(BidsResponse { bids })
 }
 
+pub fn query_top_bid(
+    deps: Deps,
+    env: Env,
+    collection: String,
+    token_id: String,
+    denom: String,
+) -> StdResult<BidResponse> {
+    let bid = bids()
+        .idx
+        .token_price
+        .sub_prefix(((collection, token_id), denom))
+        .range(deps.storage, None, None, Order::Descending)
+        .map(|item| item.map(|(_, b)| b))
+        .find(|item| match item {
+            Ok(bid) => bid.expires_at > env.block.time,
+            Err(_) => true,
+        })
+        .transpose()?;
+
+    Ok(BidResponse { bid })
+}
+
 pub fn query_bids_by_bidder_sorted_by_expiry(
     deps: Deps,
     bidder: String,
@@ -440,6 +757,235 @@ pub fn query_bids_by_bidder_sorted_by_expiry(
 }
 
 
+pub fn query_asks_by_expiry(
+    deps: Deps,
+    expires_before: Timestamp,
+    start_after: Option<AskExpiryOffset>,
+    limit: Option<u32>,
+) -> StdResult<AsksResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+    let start = start_after.map(|offset| {
+        Bound::exclusive((offset.expires_at.seconds(), ask_key(&offset.collection, &offset.token_id)))
+    });
+
+    let asks = asks()
+        .idx
+        .expires_at
+        .range(deps.storage, start, None, Order::Ascending)
+        .take_while(|item| item.as_ref().map_or(true, |(_, ask)| ask.expires_at < expires_before))
+        .take(limit)
+        .map(|item| item.map(|(_, a)| a))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    asks_response(deps, asks)
+}
+
+pub fn query_bids_by_expiry(
+    deps: Deps,
+    expires_before: Timestamp,
+    start_after: Option<BidExpiryOffset>,
+    limit: Option<u32>,
+) -> StdResult<BidsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+    let start = start_after.map(|offset| {
+        Bound::exclusive((offset.expires_at.seconds(), bid_key(&offset.collection, &offset.token_id, &offset.bidder)))
+    });
+
+    let bids = bids()
+        .idx
+        .expires_at
+        .range(deps.storage, start, None, Order::Ascending)
+        .take_while(|item| item.as_ref().map_or(true, |(_, bid)| bid.expires_at < expires_before))
+        .take(limit)
+        .map(|item| item.map(|(_, b)| b))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(BidsResponse { bids })
+}
+
+pub fn query_collection_bids_by_expiry(
+    deps: Deps,
+    expires_before: Timestamp,
+    start_after: Option<CollectionBidExpiryOffset>,
+    limit: Option<u32>,
+) -> StdResult<CollectionBidsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+    let start = start_after.map(|offset| {
+        Bound::exclusive((offset.expires_at.seconds(), collection_bid_key(&offset.collection, &offset.bidder)))
+    });
+
+    let bids = collection_bids()
+        .idx
+        .expires_at
+        .range(deps.storage, start, None, Order::Ascending)
+        .take_while(|item| item.as_ref().map_or(true, |(_, bid)| bid.expires_at < expires_before))
+        .take(limit)
+        .map(|item| item.map(|(_, b)| b))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CollectionBidsResponse { bids })
+}
+
+pub fn query_counter_offer(
+    deps: Deps,
+    collection: String,
+    token_id: String,
+    bidder: String,
+) -> StdResult<CounterOfferResponse> {
+    let counter_offer = counter_offers().may_load(deps.storage, bid_key(&collection, &token_id, &bidder))?;
+
+    Ok(CounterOfferResponse { counter_offer })
+}
+
+pub fn query_counter_offers_by_bidder(
+    deps: Deps,
+    bidder: String,
+    start_after: Option<CollectionOffset>,
+    limit: Option<u32>,
+) -> StdResult<CounterOffersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+    let start = start_after.map(|start| Bound::exclusive(bid_key(&start.collection, &start.token_id, &bidder)));
+
+    let counter_offers = counter_offers()
+        .idx
+        .bidder
+        .prefix(bidder)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| res.map(|item| item.1))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CounterOffersResponse { counter_offers })
+}
+
+pub fn query_counter_offers_by_seller(
+    deps: Deps,
+    seller: String,
+    start_after: Option<CollectionOffsetBid>,
+    limit: Option<u32>,
+) -> StdResult<CounterOffersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+    let start = start_after.map(|start| Bound::exclusive(bid_key(&start.collection, &start.token_id, &start.bidder)));
+
+    let counter_offers = counter_offers()
+        .idx
+        .seller
+        .prefix(seller)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| res.map(|item| item.1))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CounterOffersResponse { counter_offers })
+}
+
+pub fn query_offer(
+    deps: Deps,
+    collection: String,
+    token_id: String,
+    bidder: String,
+) -> StdResult<OfferResponse> {
+    let offer = offers().may_load(deps.storage, bid_key(&collection, &token_id, &bidder))?;
+
+    Ok(OfferResponse { offer })
+}
+
+pub fn query_offers_by_token(
+    deps: Deps,
+    collection: String,
+    token_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OffersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+    let start = start_after.map(|bidder| Bound::exclusive(bid_key(&collection, &token_id, &bidder)));
+
+    let offers = offers()
+        .idx
+        .collection_token_id
+        .prefix((collection, token_id))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| res.map(|item| item.1))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(OffersResponse { offers })
+}
+
+pub fn query_offers_by_bidder(
+    deps: Deps,
+    bidder: String,
+    start_after: Option<CollectionOffset>,
+    limit: Option<u32>,
+) -> StdResult<OffersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+    let start = start_after.map(|start| Bound::exclusive(bid_key(&start.collection, &start.token_id, &bidder)));
+
+    let offers = offers()
+        .idx
+        .bidder
+        .prefix(bidder)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| res.map(|item| item.1))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(OffersResponse { offers })
+}
+
+pub fn query_cw1155_asks(
+    deps: Deps,
+    collection: String,
+    token_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Cw1155AsksResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+    let start = start_after.map(|seller| Bound::exclusive(bid_key(&collection, &token_id, &seller)));
+
+    let asks = cw1155_asks()
+        .idx
+        .collection_token_id
+        .prefix((collection, token_id))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| res.map(|item| item.1))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(Cw1155AsksResponse { asks })
+}
+
+pub fn query_cw1155_bids(
+    deps: Deps,
+    collection: String,
+    token_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Cw1155BidsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+    let start = start_after.map(|bidder| Bound::exclusive(bid_key(&collection, &token_id, &bidder)));
+
+    let bids = cw1155_bids()
+        .idx
+        .collection_token_id
+        .prefix((collection, token_id))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| res.map(|item| item.1))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(Cw1155BidsResponse { bids })
+}
+
 pub fn query_bids_by_seller(
     deps: Deps,
     seller: String,
@@ -482,6 +1028,7 @@ pub fn query_sale_history(
             &collection,
             &start.token_id,
             start.time,
+            start.seq.unwrap_or(u64::MAX),
         )))
     } else {
         None
@@ -503,19 +1050,17 @@ pub fn query_sale_history_by_token_id(
     deps: Deps,
     collection: String,
     token_id: String,
-    start_after: Option<u64>,
+    start_after: Option<SaleHistoryTokenOffset>,
     limit: Option<u32>,
 ) -> StdResult<SaleHistroyResponse> {
     let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
-    let start = if let Some(start) = start_after {
-        Some(Bound::exclusive(sale_history_key(
-            &collection,
-            &token_id,
-            start,
-        )))
-    } else {
-        None
-    };
+    let start = start_after.map(|start| {
+        let (time, seq) = match start {
+            SaleHistoryTokenOffset::Time(time) => (time, u64::MAX),
+            SaleHistoryTokenOffset::Sale(sale) => (sale.time, sale.seq),
+        };
+        Bound::exclusive(sale_history_key(&collection, &token_id, time, seq))
+    });
 
     let sale_history = sale_history()
         .idx
@@ -543,6 +1088,7 @@ pub fn query_sale_history_by_buyer(
             &start.collection,
             &start.token_id,
             start.time,
+            start.seq.unwrap_or(u64::MAX),
         )))
     } else {
         None
@@ -574,6 +1120,7 @@ pub fn query_sale_history_by_seller(
             &start.collection,
             &start.token_id,
             start.time,
+            start.seq.unwrap_or(u64::MAX),
         )))
     } else {
         None
@@ -591,6 +1138,58 @@ pub fn query_sale_history_by_seller(
     Ok(SaleHistroyResponse {  sale_history })
 }
 
+/// Filters shared by the sale history time range queries
+pub struct SaleHistoryRange {
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+    pub denom: Option<String>,
+    pub start_after: Option<SaleHistoryTimeOffset>,
+}
+
+/// Walks one of the (owner, time) sale history indexes, where owner is a collection, seller or buyer.
+/// With a denom filter the matching (owner, denom, time) index is walked instead
+pub fn query_sale_history_in_range(
+    deps: Deps,
+    index: MultiIndex<(String, u64), SaleInfo, SaleHistoryKey>,
+    denom_index: MultiIndex<(String, String, u64), SaleInfo, SaleHistoryKey>,
+    owner: String,
+    range: SaleHistoryRange,
+    limit: Option<u32>,
+    order: Option<SortOrder>,
+) -> StdResult<SaleHistroyResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+    let order: Order = order.unwrap_or(SortOrder::Ascending).into();
+    let SaleHistoryRange { start_time, end_time, denom, start_after } = range;
+
+    // The smallest possible primary key, so a bound on it covers every sale at that time
+    let first_key = || sale_history_key(&String::new(), &String::new(), 0, 0);
+    let mut min = start_time.map(|time| Bound::inclusive((time, first_key())));
+    let mut max = end_time.map(|time| Bound::exclusive((time, first_key())));
+
+    if let Some(offset) = start_after {
+        let bound = Bound::exclusive((
+            offset.time,
+            sale_history_key(&offset.collection, &offset.token_id, offset.time, offset.seq),
+        ));
+        match order {
+            Order::Ascending => min = Some(bound),
+            Order::Descending => max = Some(bound),
+        }
+    }
+
+    let prefix = match denom {
+        Some(denom) => denom_index.sub_prefix((owner, denom)),
+        None => index.sub_prefix(owner),
+    };
+    let sale_history = prefix
+        .range(deps.storage, min, max, order)
+        .map(|item| item.map(|(_, b)| b))
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(SaleHistroyResponse { sale_history })
+}
+
 
 pub fn query_tvl_by_collection(
     deps: Deps,
@@ -767,3 +1366,213 @@ pub fn query_collection_bid_by_collection(
 
     Ok(CollectionBidsResponse { bids })
 }
+
+
+pub fn query_highest_collection_bid(
+    deps: Deps,
+    env: Env,
+    collection: String,
+    denom: String,
+) -> StdResult<CollectionBidResponse> {
+    let bid = collection_bids()
+        .idx
+        .collection_price
+        .sub_prefix((collection, denom))
+        .range(deps.storage, None, None, Order::Descending)
+        .map(|item| item.map(|(_, b)| b))
+        .find(|item| match item {
+            Ok(bid) => bid.expires_at > env.block.time,
+            Err(_) => true,
+        })
+        .transpose()?;
+
+    Ok(CollectionBidResponse { bid })
+}
+
+
+pub fn query_collection_stats(
+    deps: Deps,
+    env: Env,
+    collection: String,
+    denom: String,
+) -> StdResult<CollectionStatsResponse> {
+    let stats = COLLECTION_STATS.may_load(deps.storage, (&collection, &denom))?;
+    let total_volume = tvl()
+        .may_load(deps.storage, (collection.clone(), denom.clone()))?
+        .map(|tvl| tvl.amount)
+        .unwrap_or_default();
+
+    let crr_time = env.block.time.seconds();
+    //only whole buckets inside the window count, the bucket holding the window start is left out
+    let volume_since = |window: u64| -> StdResult<Uint128> {
+        let start_bucket = crr_time.saturating_sub(window) / VOLUME_BUCKET_SECONDS;
+        VOLUME_BUCKETS
+            .prefix((&collection, &denom))
+            .range(deps.storage, Some(Bound::exclusive(start_bucket)), None, Order::Ascending)
+            .map(|item| item.map(|(_, volume)| volume))
+            .sum()
+    };
+
+    let floor_price = asks()
+        .idx
+        .collection_price
+        .sub_prefix((collection.clone(), denom.clone()))
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, ask)| ask))
+        .find(|item| match item {
+            Ok(ask) => ask.expires_at > env.block.time,
+            Err(_) => true,
+        })
+        .transpose()?
+        .map(|ask| ask.list_price.amount);
+
+    Ok(CollectionStatsResponse {
+        sale_count: stats.as_ref().map(|stats| stats.sale_count).unwrap_or_default(),
+        last_sale_price: stats.as_ref().map(|stats| stats.last_sale_price),
+        last_sale_time: stats.as_ref().map(|stats| stats.last_sale_time),
+        all_time_high: stats.as_ref().map(|stats| stats.all_time_high),
+        total_volume,
+        volume_24h: volume_since(DAY_SECONDS)?,
+        volume_7d: volume_since(7 * DAY_SECONDS)?,
+        volume_30d: volume_since(30 * DAY_SECONDS)?,
+        floor_price,
+        collection,
+        denom,
+    })
+}
+
+
+pub fn query_candles(
+    deps: Deps,
+    collection: String,
+    denom: String,
+    interval: CandleInterval,
+    start_time: Option<u64>,
+    end_time: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<CandlesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+    let candles = interval
+        .candles()
+        .prefix((&collection, &denom))
+        .range(
+            deps.storage,
+            start_time.map(Bound::inclusive),
+            end_time.map(Bound::exclusive),
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(_, candle)| candle))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CandlesResponse { candles })
+}
+
+pub fn query_activities_by_collection(
+    deps: Deps,
+    collection: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ActivitiesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+    let end = start_after.map(Bound::exclusive);
+
+    let activities = activities()
+        .idx
+        .collection
+        .prefix(collection)
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|item| item.map(|(_, activity)| activity))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ActivitiesResponse { activities })
+}
+
+pub fn query_activities_by_token(
+    deps: Deps,
+    collection: String,
+    token_id: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ActivitiesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+    let end = start_after.map(Bound::exclusive);
+
+    let activities = activities()
+        .idx
+        .collection_token_id
+        .prefix((collection, token_id))
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|item| item.map(|(_, activity)| activity))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ActivitiesResponse { activities })
+}
+
+pub fn query_activities_by_user(
+    deps: Deps,
+    user: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ActivitiesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+    let end = start_after.map(Bound::exclusive);
+
+    let activities = USER_ACTIVITIES
+        .prefix(&user)
+        .keys(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|id| activities().load(deps.storage, id?))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ActivitiesResponse { activities })
+}
+
+pub fn query_hooks(deps: Deps, hook_type: HookType) -> StdResult<HooksResponse> {
+    let hooks = hook_type.hooks().may_load(deps.storage)?.unwrap_or_default();
+    Ok(HooksResponse { hooks })
+}
+
+pub fn query_reward_program(deps: Deps) -> StdResult<RewardProgramResponse> {
+    let config = REWARD_CONFIG.may_load(deps.storage)?;
+    let pool = REWARD_POOL.may_load(deps.storage)?.unwrap_or_default();
+    Ok(RewardProgramResponse { config, pool })
+}
+
+pub fn query_cleanup_bounty(deps: Deps) -> StdResult<CleanupBountyResponse> {
+    let bounty = CLEANUP_BOUNTY.may_load(deps.storage)?;
+    let pool = CLEANUP_POOL.may_load(deps.storage)?.unwrap_or_default();
+    Ok(CleanupBountyResponse { bounty, pool })
+}
+
+pub fn query_reward_rate(deps: Deps, collection: String, denom: String) -> StdResult<Decimal> {
+    let rate = REWARD_RATES.may_load(deps.storage, (&collection, &denom))?;
+    Ok(rate.unwrap_or_default())
+}
+
+pub fn query_pending_rewards(deps: Deps, env: Env, address: String) -> StdResult<PendingRewardsResponse> {
+    let now = env.block.time.seconds();
+    let mut claimable = Uint128::zero();
+    let mut accruing = Uint128::zero();
+    for item in EPOCH_REWARDS.prefix(&address).range(deps.storage, None, None, Order::Ascending) {
+        let (epoch_end, amount) = item?;
+        if epoch_end <= now {
+            claimable += amount;
+        } else {
+            accruing += amount;
+        }
+    }
+    Ok(PendingRewardsResponse { claimable, accruing })
+}
+
+pub fn query_finder_earnings(deps: Deps, finder: String) -> StdResult<FinderEarningsResponse> {
+    let earnings = FINDER_EARNINGS
+        .prefix(&finder)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Asset { denom, amount }))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(FinderEarningsResponse { earnings })
+}
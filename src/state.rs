@@ -1,4 +1,4 @@
-use cosmwasm_std::{Uint128, Decimal, Timestamp, BlockInfo};
+use cosmwasm_std::{Uint128, Decimal, Timestamp, BlockInfo, Binary};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use cw_storage_plus::{Item,Map,MultiIndex,IndexList,Index,IndexedMap};
@@ -9,6 +9,50 @@ pub const COLLECTIONINFO: Map<&str, CollectionInfo> = Map::new("collection_info"
 pub const TOKENADDRESS: Map<&str, String> = Map::new("token_address");
 pub const COINDENOM: Map<&str, bool> = Map::new("coin_denom");
 pub const TVL:Map<(&str,&str),Uint128> = Map::new("tvl_config");
+/// Last sale sequence number handed out
+pub const SALE_SEQ: Item<u64> = Item::new("sale_seq");
+pub const COLLECTION_STATS: Map<(&str,&str), CollectionStats> = Map::new("collection_stats");
+/// Sale volume per (collection, denom, hour bucket), used for the rolling volume windows
+pub const VOLUME_BUCKETS: Map<(&str,&str,u64), Uint128> = Map::new("volume_buckets");
+
+/// OHLC candles per (collection, denom, candle start time)
+pub const HOURLY_CANDLES: Map<(&str,&str,u64), Candle> = Map::new("hourly_candles");
+pub const DAILY_CANDLES: Map<(&str,&str,u64), Candle> = Map::new("daily_candles");
+
+/// Last activity id handed out
+pub const ACTIVITY_SEQ: Item<u64> = Item::new("activity_seq");
+/// Activity ids per user, covering both the actor and the counterparty of an activity
+pub const USER_ACTIVITIES: Map<(&str,u64), bool> = Map::new("user_activities");
+
+/// Hook contracts notified on sales, new asks and new bids
+pub const SALE_HOOKS: Item<Vec<String>> = Item::new("sale_hooks");
+pub const ASK_HOOKS: Item<Vec<String>> = Item::new("ask_hooks");
+pub const BID_HOOKS: Item<Vec<String>> = Item::new("bid_hooks");
+
+/// Finder fees earned per (finder, denom)
+pub const FINDER_EARNINGS: Map<(&str,&str), Uint128> = Map::new("finder_earnings");
+pub const REWARD_CONFIG: Item<RewardConfig> = Item::new("reward_config");
+/// Reward tokens funded and not yet accrued to traders
+pub const REWARD_POOL: Item<Uint128> = Item::new("reward_pool");
+/// Rewards accrued out of the pool and not yet claimed, owed in the current reward token
+pub const REWARD_OUTSTANDING: Item<Uint128> = Item::new("reward_outstanding");
+/// Reward tokens per unit of volume, per (collection, denom)
+pub const REWARD_RATES: Map<(&str,&str), Decimal> = Map::new("reward_rates");
+/// Accrued rewards per (user, epoch end), claimable once the epoch has ended
+pub const EPOCH_REWARDS: Map<(&str,u64), Uint128> = Map::new("epoch_rewards");
+/// Optional bounty paid per order removed by the cleanup crank, at most `CLEANUP_BOUNTY_SHARE` of its escrow
+pub const CLEANUP_BOUNTY: Item<Asset> = Item::new("cleanup_bounty");
+/// Bounty coins funded and not yet paid out
+pub const CLEANUP_POOL: Item<Uint128> = Item::new("cleanup_pool");
+/// Orders only earn the cleanup bounty once they have been expired for this long
+pub const CLEANUP_GRACE_SECONDS: u64 = 3600;
+/// Largest share of an order's escrow paid to the cleanup caller for removing it
+pub const CLEANUP_BOUNTY_SHARE: Decimal = Decimal::percent(2);
+/// How far the expiry index backfill has got
+pub const EXPIRY_BACKFILL: Item<ExpiryBackfill> = Item::new("expiry_backfill");
+
+pub const VOLUME_BUCKET_SECONDS: u64 = 3600;
+pub const DAY_SECONDS: u64 = 86400;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
@@ -18,6 +62,9 @@ pub struct State {
 }
 
 
+/// Expiry model shared by asks, bids and collection bids: an order is expired from `expires_at` on.
+/// An expired ask can't be bought, bid on, repriced or sold by accepting a bid, its seller can only withdraw it.
+/// An expired bid can't be accepted, its bidder can only remove it. `CleanupExpired` settles both for anyone
 pub trait Order {
     fn expires_at(&self) -> Timestamp;
 
@@ -33,6 +80,18 @@ pub struct Ask {
     pub list_price: Asset,
     pub expires_at: Timestamp,
     pub collection: String,
+    /// Only this address can buy or bid on the listing
+    #[serde(default)]
+    pub reserved_for: Option<String>,
+    /// An auction bid in the list price denom at or above this amount buys the NFT right away
+    #[serde(default)]
+    pub buy_now_price: Option<Uint128>,
+    /// Auction bids must be in the list price denom and at least this amount
+    #[serde(default)]
+    pub reserve_price: Option<Uint128>,
+    /// Overrides the collection minimum bid increment for this listing
+    #[serde(default)]
+    pub min_bid_increment: Option<Decimal>,
 }
 
 
@@ -42,6 +101,18 @@ impl Order for Ask {
     }
 }
 
+impl Ask {
+    pub fn is_reserved_for_other(&self, buyer: &str) -> bool {
+        self.reserved_for.as_ref().is_some_and(|reserved_for| reserved_for != buyer)
+    }
+
+    /// Returns the buy-now price if the bid reaches it
+    pub fn buy_now_price_for(&self, bid: &Asset) -> Option<Uint128> {
+        self.buy_now_price
+            .filter(|buy_now_price| bid.denom == self.list_price.denom && bid.amount >= *buy_now_price)
+    }
+}
+
 /// Primary key for asks: (collection, token_id)
 pub type AskKey<'a> = (String, String);
 /// Convenience ask key constructor
@@ -53,11 +124,15 @@ pub fn ask_key<'a>(collection: &'a String, token_id: &'a String) -> AskKey<'a> {
 pub struct AskIndicies<'a> {
     pub collection: MultiIndex<'a, String, Ask, AskKey<'a>>,
     pub seller: MultiIndex<'a, String, Ask, AskKey<'a>>,
+    // Cannot include `Uint128` in index, converted `Uint128` to `u128`
+    pub collection_price: MultiIndex<'a, (String, String, u128), Ask, AskKey<'a>>,
+    // Cannot include `Timestamp` in index, converted `Timestamp` to `seconds` and stored as `u64`
+    pub expires_at: MultiIndex<'a, u64, Ask, AskKey<'a>>,
 }
 
 impl<'a> IndexList<Ask> for AskIndicies<'a> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Ask>> + '_> {
-        let v: Vec<&dyn Index<Ask>> = vec![&self.collection, &self.seller];
+        let v: Vec<&dyn Index<Ask>> = vec![&self.collection, &self.seller, &self.collection_price, &self.expires_at];
         Box::new(v.into_iter())
     }
 }
@@ -66,6 +141,12 @@ pub fn asks<'a>() -> IndexedMap<'a, AskKey<'a>, Ask, AskIndicies<'a>> {
     let indexes = AskIndicies {
         collection: MultiIndex::new(|d: &Ask| d.collection.clone(), "asks", "asks__collection"),
         seller: MultiIndex::new(|d: &Ask| d.seller.clone(), "asks", "asks__seller"),
+        collection_price: MultiIndex::new(
+            |d: &Ask| (d.collection.clone(), d.list_price.denom.clone(), d.list_price.amount.u128()),
+            "asks",
+            "asks__collection_price",
+        ),
+        expires_at: MultiIndex::new(|d: &Ask| d.expires_at.seconds(), "asks", "asks__expires_at"),
     };
     IndexedMap::new("asks", indexes)
 }
@@ -80,7 +161,10 @@ pub struct Bid {
     pub list_price: Asset,
     pub expires_at: Timestamp,
     pub token_address: Option<String>,
-    pub seller: String
+    pub seller: String,
+    /// Referrer paid a finder fee when this bid is accepted
+    #[serde(default)]
+    pub finder: Option<String>
 }
 
 
@@ -106,6 +190,9 @@ pub struct BidIndicies<'a> {
     pub seller: MultiIndex<'a, String, Bid, BidKey>,
      // Cannot include `Timestamp` in index, converted `Timestamp` to `seconds` and stored as `u64`
     pub bidder_expires_at: MultiIndex<'a, (String, u64), Bid, BidKey>,
+    // Bids are placed on a single token, so the price index is keyed by ((collection, token_id), denom, amount)
+    pub token_price: MultiIndex<'a, ((String, String), String, u128), Bid, BidKey>,
+    pub expires_at: MultiIndex<'a, u64, Bid, BidKey>,
 }
 
 impl<'a> IndexList<Bid> for BidIndicies<'a> {
@@ -116,6 +203,8 @@ impl<'a> IndexList<Bid> for BidIndicies<'a> {
             &self.bidder,
             &self.seller,
             &self.bidder_expires_at,
+            &self.token_price,
+            &self.expires_at,
         ];
         Box::new(v.into_iter())
     }
@@ -136,6 +225,12 @@ pub fn bids<'a>() -> IndexedMap<'a, BidKey, Bid, BidIndicies<'a>> {
             "bids",
             "bids__bidder_expires_at",
         ),   
+        token_price: MultiIndex::new(
+            |d: &Bid| ((d.collection.clone(), d.token_id.clone()), d.list_price.denom.clone(), d.list_price.amount.u128()),
+            "bids",
+            "bids__token_price",
+        ),
+        expires_at: MultiIndex::new(|d: &Bid| d.expires_at.seconds(), "bids", "bids__expires_at"),
     };
     IndexedMap::new("bids", indexes)
 }
@@ -149,38 +244,114 @@ pub struct SaleInfo {
     pub amount:Uint128,
     pub time : u64,
     pub collection:String,
-    pub token_id:String
-}
-
-/// Primary key for bids: (collection, token_id, bidder)
-pub type SaleHistoryKey = (String, String, u64);
-/// Convenience bid key constructor
-pub fn sale_history_key(collection: &String, token_id: &String, time: u64) -> SaleHistoryKey {
-    (collection.clone(), token_id.clone(), time)
-}
-
-
-/// Defines incides for accessing bids
-pub struct SaleHistoryIndicies<'a> {
-    pub collection: MultiIndex<'a, String, SaleInfo, SaleHistoryKey>,
-    pub collection_token_id: MultiIndex<'a, (String, String), SaleInfo, SaleHistoryKey>,
-    pub buyer: MultiIndex<'a, String, SaleInfo, SaleHistoryKey>,
-    pub seller: MultiIndex<'a, String, SaleInfo, SaleHistoryKey>
-}
-
-impl<'a> IndexList<SaleInfo> for SaleHistoryIndicies<'a> {
+    pub token_id:String,
+    /// Sale sequence number, assigned by the contract when the sale is recorded. Sales moved by
+    /// `MigrateSaleHistory` get theirs in migration order, so only `(time, seq)` orders all sales by time
+    #[serde(default)]
+    pub seq: u64,
+    /// Units sold in a cw1155 sale, where `amount` is the unit price. `None` for single NFTs
+    #[serde(default)]
+    pub quantity: Option<Uint128>
+}
+
+/// Primary key for sale history: (collection, token_id, (time, seq))
+pub type SaleHistoryKey = (String, String, (u64, u64));
+/// Convenience sale history key constructor
+pub fn sale_history_key(collection: &String, token_id: &String, time: u64, seq: u64) -> SaleHistoryKey {
+    (collection.clone(), token_id.clone(), (time, seq))
+}
+
+/// Primary key for sale history before sequence numbers: (collection, token_id, time)
+pub type LegacySaleHistoryKey = (String, String, u64);
+
+
+/// Defines incides for accessing sale history
+pub struct SaleHistoryIndicies<'a, K = SaleHistoryKey> {
+    pub collection: MultiIndex<'a, String, SaleInfo, K>,
+    pub collection_token_id: MultiIndex<'a, (String, String), SaleInfo, K>,
+    pub buyer: MultiIndex<'a, String, SaleInfo, K>,
+    pub seller: MultiIndex<'a, String, SaleInfo, K>,
+    pub collection_time: MultiIndex<'a, (String, u64), SaleInfo, K>,
+    pub buyer_time: MultiIndex<'a, (String, u64), SaleInfo, K>,
+    pub seller_time: MultiIndex<'a, (String, u64), SaleInfo, K>,
+    pub collection_denom_time: MultiIndex<'a, (String, String, u64), SaleInfo, K>,
+    pub buyer_denom_time: MultiIndex<'a, (String, String, u64), SaleInfo, K>,
+    pub seller_denom_time: MultiIndex<'a, (String, String, u64), SaleInfo, K>
+}
+
+impl<'a, K> IndexList<SaleInfo> for SaleHistoryIndicies<'a, K> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<SaleInfo>> + '_> {
         let v: Vec<&dyn Index<SaleInfo>> = vec![
             &self.collection,
             &self.collection_token_id,
             &self.buyer,
-            &self.seller
+            &self.seller,
+            &self.collection_time,
+            &self.buyer_time,
+            &self.seller_time,
+            &self.collection_denom_time,
+            &self.buyer_denom_time,
+            &self.seller_denom_time
         ];
         Box::new(v.into_iter())
     }
 }
 
 pub fn sale_history<'a>() -> IndexedMap<'a, SaleHistoryKey, SaleInfo, SaleHistoryIndicies<'a>> {
+    let indexes = SaleHistoryIndicies {
+        collection: MultiIndex::new(|d: &SaleInfo| d.collection.clone(), "sale_hist", "sale_hist__collection"),
+        collection_token_id: MultiIndex::new(
+            |d: &SaleInfo| (d.collection.clone(), d.token_id.clone()),
+            "sale_hist",
+            "sale_hist__collection_token_id",
+        ),
+        buyer: MultiIndex::new(
+            |d: &SaleInfo| d.to.clone(),
+            "sale_hist",
+            "sale_hist__buyer",
+        ),
+        seller: MultiIndex::new(
+            |d: &SaleInfo| d.from.clone(),
+            "sale_hist",
+            "sale_hist__seller",
+        ),
+        collection_time: MultiIndex::new(
+            |d: &SaleInfo| (d.collection.clone(), d.time),
+            "sale_hist",
+            "sale_hist__collection_time",
+        ),
+        buyer_time: MultiIndex::new(
+            |d: &SaleInfo| (d.to.clone(), d.time),
+            "sale_hist",
+            "sale_hist__buyer_time",
+        ),
+        seller_time: MultiIndex::new(
+            |d: &SaleInfo| (d.from.clone(), d.time),
+            "sale_hist",
+            "sale_hist__seller_time",
+        ),
+        collection_denom_time: MultiIndex::new(
+            |d: &SaleInfo| (d.collection.clone(), d.denom.clone(), d.time),
+            "sale_hist",
+            "sale_hist__collection_denom_time",
+        ),
+        buyer_denom_time: MultiIndex::new(
+            |d: &SaleInfo| (d.to.clone(), d.denom.clone(), d.time),
+            "sale_hist",
+            "sale_hist__buyer_denom_time",
+        ),
+        seller_denom_time: MultiIndex::new(
+            |d: &SaleInfo| (d.from.clone(), d.denom.clone(), d.time),
+            "sale_hist",
+            "sale_hist__seller_denom_time",
+        )
+    };
+    IndexedMap::new("sale_hist", indexes)
+}
+
+/// Sale history keyed without sequence numbers, where a second sale of a token in the same second
+/// overwrote the first. Only read to move existing entries into `sale_history()`
+pub fn legacy_sale_history<'a>() -> IndexedMap<'a, LegacySaleHistoryKey, SaleInfo, SaleHistoryIndicies<'a, LegacySaleHistoryKey>> {
     let indexes = SaleHistoryIndicies {
         collection: MultiIndex::new(|d: &SaleInfo| d.collection.clone(), "sale_history", "sale_history_collection"),
         collection_token_id: MultiIndex::new(
@@ -197,6 +368,36 @@ pub fn sale_history<'a>() -> IndexedMap<'a, SaleHistoryKey, SaleInfo, SaleHistor
             |d: &SaleInfo| d.from.clone(),
             "sale_history",
             "sale_history__seller",
+        ),
+        collection_time: MultiIndex::new(
+            |d: &SaleInfo| (d.collection.clone(), d.time),
+            "sale_history",
+            "sale_history__collection_time",
+        ),
+        buyer_time: MultiIndex::new(
+            |d: &SaleInfo| (d.to.clone(), d.time),
+            "sale_history",
+            "sale_history__buyer_time",
+        ),
+        seller_time: MultiIndex::new(
+            |d: &SaleInfo| (d.from.clone(), d.time),
+            "sale_history",
+            "sale_history__seller_time",
+        ),
+        collection_denom_time: MultiIndex::new(
+            |d: &SaleInfo| (d.collection.clone(), d.denom.clone(), d.time),
+            "sale_history",
+            "sale_history__collection_denom_time",
+        ),
+        buyer_denom_time: MultiIndex::new(
+            |d: &SaleInfo| (d.to.clone(), d.denom.clone(), d.time),
+            "sale_history",
+            "sale_history__buyer_denom_time",
+        ),
+        seller_denom_time: MultiIndex::new(
+            |d: &SaleInfo| (d.from.clone(), d.denom.clone(), d.time),
+            "sale_history",
+            "sale_history__seller_denom_time",
         )
     };
     IndexedMap::new("sale_history", indexes)
@@ -248,13 +449,97 @@ pub fn tvl<'a>() -> IndexedMap<'a, TvlKey, TvlInfo, TvlIndicies<'a>> {
     IndexedMap::new("tvl", indexes)
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CollectionStats {
+    pub collection: String,
+    pub denom: String,
+    pub sale_count: u64,
+    pub last_sale_price: Uint128,
+    pub last_sale_time: u64,
+    pub all_time_high: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Candle {
+    pub start_time: u64,
+    pub open: Uint128,
+    pub high: Uint128,
+    pub low: Uint128,
+    pub close: Uint128,
+    pub volume: Uint128,
+    pub sale_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CandleInterval {
+    Hour,
+    Day
+}
+
+impl CandleInterval {
+    pub fn seconds(&self) -> u64 {
+        match self {
+            CandleInterval::Hour => VOLUME_BUCKET_SECONDS,
+            CandleInterval::Day => DAY_SECONDS,
+        }
+    }
+
+    pub fn candles(&self) -> Map<'static, (&'static str, &'static str, u64), Candle> {
+        match self {
+            CandleInterval::Hour => HOURLY_CANDLES,
+            CandleInterval::Day => DAILY_CANDLES,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardConfig {
+    /// cw20 token rewards are paid in
+    pub token_address: String,
+    pub epoch_seconds: u64,
+    /// Epochs are counted from this time
+    pub start_time: u64,
+}
+
+impl RewardConfig {
+    /// End of the epoch containing `time`
+    pub fn epoch_end(&self, time: u64) -> u64 {
+        let elapsed = time.saturating_sub(self.start_time);
+        self.start_time + (elapsed / self.epoch_seconds + 1) * self.epoch_seconds
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HookType {
+    Sale,
+    Ask,
+    Bid
+}
+
+impl HookType {
+    pub fn hooks(&self) -> Item<'static, Vec<String>> {
+        match self {
+            HookType::Sale => SALE_HOOKS,
+            HookType::Ask => ASK_HOOKS,
+            HookType::Bid => BID_HOOKS,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CollectionBid {
     pub collection: String,
     pub bidder: String,
     pub list_price: Asset,
     pub expires_at: Timestamp,
-    pub token_address: Option<String>
+    pub token_address: Option<String>,
+    /// Referrer paid a finder fee when this bid is accepted
+    #[serde(default)]
+    pub finder: Option<String>
 }
 
 impl Order for CollectionBid {
@@ -275,7 +560,10 @@ pub struct CollectionBidIndicies<'a> {
     pub collection: MultiIndex<'a, String, CollectionBid, CollectionBidKey>,
     pub bidder: MultiIndex<'a, String, CollectionBid, CollectionBidKey>,
     // Cannot include `Timestamp` in index, converted `Timestamp` to `seconds` and stored as `u64`
-    pub bidder_expires_at: MultiIndex<'a, (String, u64), CollectionBid, CollectionBidKey>
+    pub bidder_expires_at: MultiIndex<'a, (String, u64), CollectionBid, CollectionBidKey>,
+    // Cannot include `Uint128` in index, converted `Uint128` to `u128`
+    pub collection_price: MultiIndex<'a, (String, String, u128), CollectionBid, CollectionBidKey>,
+    pub expires_at: MultiIndex<'a, u64, CollectionBid, CollectionBidKey>
 }
 
 impl<'a> IndexList<CollectionBid> for CollectionBidIndicies<'a> {
@@ -284,6 +572,8 @@ impl<'a> IndexList<CollectionBid> for CollectionBidIndicies<'a> {
             &self.collection,
             &self.bidder,
             &self.bidder_expires_at,
+            &self.collection_price,
+            &self.expires_at,
         ];
         Box::new(v.into_iter())
     }
@@ -307,12 +597,200 @@ pub fn collection_bids<'a>(
             "col_bids",
             "col_bids__bidder_expires_at",
         ),
+        collection_price: MultiIndex::new(
+            |d: &CollectionBid| (d.collection.clone(), d.list_price.denom.clone(), d.list_price.amount.u128()),
+            "col_bids",
+            "col_bids__collection_price",
+        ),
+        expires_at: MultiIndex::new(
+            |d: &CollectionBid| d.expires_at.seconds(),
+            "col_bids",
+            "col_bids__expires_at",
+        ),
     };
     IndexedMap::new("col_bids", indexes)
 }
 
 
 
+/// Price a seller proposes to one bidder on a listed token. The bidder accepts by sending
+/// the difference between the price and their bid, both in the bid denom
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CounterOffer {
+    pub collection: String,
+    pub token_id: String,
+    pub seller: String,
+    pub bidder: String,
+    pub price: Asset,
+    pub expires_at: Timestamp,
+}
+
+impl Order for CounterOffer {
+    fn expires_at(&self) -> Timestamp {
+        self.expires_at
+    }
+}
+
+/// Defines incides for accessing counter-offers, keyed like bids by (collection, token_id, bidder)
+pub struct CounterOfferIndicies<'a> {
+    pub collection_token_id: MultiIndex<'a, (String, String), CounterOffer, BidKey>,
+    pub bidder: MultiIndex<'a, String, CounterOffer, BidKey>,
+    pub seller: MultiIndex<'a, String, CounterOffer, BidKey>,
+}
+
+impl<'a> IndexList<CounterOffer> for CounterOfferIndicies<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<CounterOffer>> + '_> {
+        let v: Vec<&dyn Index<CounterOffer>> = vec![&self.collection_token_id, &self.bidder, &self.seller];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn counter_offers<'a>() -> IndexedMap<'a, BidKey, CounterOffer, CounterOfferIndicies<'a>> {
+    let indexes = CounterOfferIndicies {
+        collection_token_id: MultiIndex::new(
+            |d: &CounterOffer| (d.collection.clone(), d.token_id.clone()),
+            "counter_offers",
+            "counter_offers__collection_token_id",
+        ),
+        bidder: MultiIndex::new(|d: &CounterOffer| d.bidder.clone(), "counter_offers", "counter_offers__bidder"),
+        seller: MultiIndex::new(|d: &CounterOffer| d.seller.clone(), "counter_offers", "counter_offers__seller"),
+    };
+    IndexedMap::new("counter_offers", indexes)
+}
+
+/// Escrowed cw1155 listing. Several sellers can list the same token id, buyers fill any quantity up to `quantity`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw1155Ask {
+    pub collection: String,
+    pub token_id: String,
+    pub seller: String,
+    pub unit_price: Asset,
+    pub token_address: Option<String>,
+    pub quantity: Uint128,
+    pub expires_at: Timestamp,
+}
+
+impl Order for Cw1155Ask {
+    fn expires_at(&self) -> Timestamp {
+        self.expires_at
+    }
+}
+
+/// cw1155 bid escrowing `unit_price * quantity`. Sellers fill any quantity up to `quantity`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw1155Bid {
+    pub collection: String,
+    pub token_id: String,
+    pub bidder: String,
+    pub unit_price: Asset,
+    pub token_address: Option<String>,
+    pub quantity: Uint128,
+    pub expires_at: Timestamp,
+}
+
+impl Order for Cw1155Bid {
+    fn expires_at(&self) -> Timestamp {
+        self.expires_at
+    }
+}
+
+/// Defines incides for accessing cw1155 asks, keyed by (collection, token_id, seller)
+pub struct Cw1155AskIndicies<'a> {
+    pub collection_token_id: MultiIndex<'a, (String, String), Cw1155Ask, BidKey>,
+    pub expires_at: MultiIndex<'a, u64, Cw1155Ask, BidKey>,
+}
+
+impl<'a> IndexList<Cw1155Ask> for Cw1155AskIndicies<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Cw1155Ask>> + '_> {
+        let v: Vec<&dyn Index<Cw1155Ask>> = vec![&self.collection_token_id, &self.expires_at];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn cw1155_asks<'a>() -> IndexedMap<'a, BidKey, Cw1155Ask, Cw1155AskIndicies<'a>> {
+    let indexes = Cw1155AskIndicies {
+        collection_token_id: MultiIndex::new(
+            |d: &Cw1155Ask| (d.collection.clone(), d.token_id.clone()),
+            "cw1155_asks",
+            "cw1155_asks__collection_token_id",
+        ),
+        expires_at: MultiIndex::new(|d: &Cw1155Ask| d.expires_at.seconds(), "cw1155_asks", "cw1155_asks__expires_at"),
+    };
+    IndexedMap::new("cw1155_asks", indexes)
+}
+
+/// Defines incides for accessing cw1155 bids, keyed by (collection, token_id, bidder)
+pub struct Cw1155BidIndicies<'a> {
+    pub collection_token_id: MultiIndex<'a, (String, String), Cw1155Bid, BidKey>,
+    pub expires_at: MultiIndex<'a, u64, Cw1155Bid, BidKey>,
+}
+
+impl<'a> IndexList<Cw1155Bid> for Cw1155BidIndicies<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Cw1155Bid>> + '_> {
+        let v: Vec<&dyn Index<Cw1155Bid>> = vec![&self.collection_token_id, &self.expires_at];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn cw1155_bids<'a>() -> IndexedMap<'a, BidKey, Cw1155Bid, Cw1155BidIndicies<'a>> {
+    let indexes = Cw1155BidIndicies {
+        collection_token_id: MultiIndex::new(
+            |d: &Cw1155Bid| (d.collection.clone(), d.token_id.clone()),
+            "cw1155_bids",
+            "cw1155_bids__collection_token_id",
+        ),
+        expires_at: MultiIndex::new(|d: &Cw1155Bid| d.expires_at.seconds(), "cw1155_bids", "cw1155_bids__expires_at"),
+    };
+    IndexedMap::new("cw1155_bids", indexes)
+}
+
+/// Offer on any token of a registered collection, listed or not. The token owner accepts by
+/// sending the NFT to the marketplace with an accept-offer payload
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Offer {
+    pub collection: String,
+    pub token_id: String,
+    pub bidder: String,
+    pub price: Asset,
+    pub token_address: Option<String>,
+    pub expires_at: Timestamp,
+    /// Referrer paid a finder fee when this offer is accepted
+    pub finder: Option<String>,
+}
+
+impl Order for Offer {
+    fn expires_at(&self) -> Timestamp {
+        self.expires_at
+    }
+}
+
+/// Defines incides for accessing offers, keyed like bids by (collection, token_id, bidder)
+pub struct OfferIndicies<'a> {
+    pub collection_token_id: MultiIndex<'a, (String, String), Offer, BidKey>,
+    pub bidder: MultiIndex<'a, String, Offer, BidKey>,
+    pub expires_at: MultiIndex<'a, u64, Offer, BidKey>,
+}
+
+impl<'a> IndexList<Offer> for OfferIndicies<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Offer>> + '_> {
+        let v: Vec<&dyn Index<Offer>> = vec![&self.collection_token_id, &self.bidder, &self.expires_at];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn offers<'a>() -> IndexedMap<'a, BidKey, Offer, OfferIndicies<'a>> {
+    let indexes = OfferIndicies {
+        collection_token_id: MultiIndex::new(
+            |d: &Offer| (d.collection.clone(), d.token_id.clone()),
+            "offers",
+            "offers__collection_token_id",
+        ),
+        bidder: MultiIndex::new(|d: &Offer| d.bidder.clone(), "offers", "offers__bidder"),
+        expires_at: MultiIndex::new(|d: &Offer| d.expires_at.seconds(), "offers", "offers__expires_at"),
+    };
+    IndexedMap::new("offers", indexes)
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Asset {
     pub denom:String,
@@ -331,7 +809,90 @@ pub struct UserInfo {
 
 pub struct CollectionInfo{
     pub nft_address :String,
-    pub royalty_portion:Decimal
+    pub royalty_portion:Decimal,
+    /// Share of the seller proceeds paid to the finder of a purchase
+    #[serde(default)]
+    pub finder_fee: Decimal,
+    /// Auction bids must beat the highest bid in the same denom by at least this share
+    #[serde(default)]
+    pub min_bid_increment: Decimal,
+    /// Auction bids on listings without a reserve price must reach this share of the list price
+    #[serde(default)]
+    pub min_bid_portion: Decimal,
+    #[serde(default)]
+    pub standard: NftStandard,
+    /// Set for ICS-721 voucher collections
+    #[serde(default)]
+    pub ics721: Option<Ics721Origin>
+}
+
+/// Where the NFTs of an ICS-721 voucher collection come from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Ics721Origin {
+    /// Bridge contract that instantiated the voucher collection
+    pub bridge: String,
+    /// Voucher class id, the `{port}/{channel}` hops the class took, most recent first, then `origin_class_id`
+    pub class_id: String,
+    /// Local port and channel the class arrived through, the way back towards the origin chain
+    pub port_id: String,
+    pub channel_id: String,
+    /// Class id on the origin chain
+    pub origin_class_id: String,
+    /// `{port}/{channel}` hops between this chain and the origin chain
+    pub trace: Vec<String>,
+}
+
+impl Ics721Origin {
+    /// Splits a voucher class id into its trace and origin class id. `None` if the class id has no hop
+    pub fn parse(bridge: String, class_id: String) -> Option<Ics721Origin> {
+        let segments: Vec<&str> = class_id.split('/').collect();
+        let mut trace = Vec::new();
+        let mut i = 0;
+        //The origin class id may contain slashes itself, hops are recognized by their channel id
+        while i + 2 < segments.len() && segments[i + 1].starts_with("channel-") {
+            trace.push(format!("{}/{}", segments[i], segments[i + 1]));
+            i += 2;
+        }
+        if trace.is_empty() {
+            return None;
+        }
+        Some(Ics721Origin {
+            port_id: segments[0].to_string(),
+            channel_id: segments[1].to_string(),
+            origin_class_id: segments[i..].join("/"),
+            trace,
+            bridge,
+            class_id,
+        })
+    }
+}
+
+/// Order book the expiry index backfill is re-saving, in the order they are walked
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryBackfillBook {
+    #[default]
+    Asks,
+    Bids,
+    CollectionBids,
+    Done
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ExpiryBackfill {
+    pub book: ExpiryBackfillBook,
+    /// Raw primary key of the last order re-saved in `book`
+    pub last_key: Option<Binary>
+}
+
+/// Token interface a collection contract speaks
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NftStandard {
+    #[default]
+    Cw721,
+    /// Semi-fungible tokens, traded by quantity at a unit price
+    Cw1155
 }
 
 
@@ -340,5 +901,66 @@ pub struct CollectionInfo{
 pub enum SaleType {
     FixedPrice,
     Auction,
-    CollectionBid
+    CollectionBid,
+    /// Offer on a token whether or not it is listed
+    Offer
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityType {
+    Listing,
+    PriceUpdate,
+    Bid,
+    BidUpdate,
+    BidRemoval,
+    CollectionBid,
+    Cancellation,
+    Sale,
+}
+
+/// Entry of the on-chain activity log. For sales the actor is the buyer and the counterparty
+/// the seller; for bids the counterparty is the seller of the ask
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Activity {
+    pub id: u64,
+    pub activity_type: ActivityType,
+    pub actor: String,
+    pub counterparty: Option<String>,
+    pub collection: String,
+    pub token_id: Option<String>,
+    pub price: Option<Asset>,
+    pub time: u64,
+}
+
+/// Primary key for activities: id
+pub type ActivityKey = u64;
+
+/// Defines indices for accessing activities
+pub struct ActivityIndicies<'a> {
+    pub collection: MultiIndex<'a, String, Activity, ActivityKey>,
+    pub collection_token_id: MultiIndex<'a, (String, String), Activity, ActivityKey>,
+}
+
+impl<'a> IndexList<Activity> for ActivityIndicies<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Activity>> + '_> {
+        let v: Vec<&dyn Index<Activity>> = vec![&self.collection, &self.collection_token_id];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn activities<'a>() -> IndexedMap<'a, ActivityKey, Activity, ActivityIndicies<'a>> {
+    let indexes = ActivityIndicies {
+        collection: MultiIndex::new(
+            |d: &Activity| d.collection.clone(),
+            "activities",
+            "activities__collection",
+        ),
+        collection_token_id: MultiIndex::new(
+            |d: &Activity| (d.collection.clone(), d.token_id.clone().unwrap_or_default()),
+            "activities",
+            "activities__collection_token_id",
+        ),
+    };
+    IndexedMap::new("activities", indexes)
 }
@@ -1,13 +1,16 @@
 #[cfg(test)]
-use crate::contract::{execute, instantiate};
-use crate::msg::{ExecuteMsg, InstantiateMsg, SellNft, BuyNft, CollectionOffset, CollectionOffsetBid, SaleHistoryOffset, SaleHistoryOffsetByUser};
-use crate::query::{query_ask_count, query_asks_by_seller, query_bids_by_bidder, query_state_info, query_ask, query_asks, query_bids, query_bids_by_seller, query_bids_by_bidder_sorted_by_expiry, query_tvl_by_collection, query_tvl_by_denom, query_sale_history, query_sale_history_by_token_id, query_sale_history_by_buyer, query_sale_history_by_seller, query_collection_bid, query_collection_bids_by_bidder, query_collection_bid_by_collection};
-use crate::state::{ask_key, asks, bid_key, bids, Ask, Bid, SaleType, Asset, UserInfo};
-
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{coin, coins, Addr, DepsMut, Timestamp, Uint128,to_binary, Env, Decimal, CosmosMsg, WasmMsg, StdResult, Response, Coin, BankMsg};
+use crate::contract::{execute, instantiate, reply as reply_entry};
+use crate::error::ContractError;
+use crate::msg::{ExecuteMsg, InstantiateMsg, SellNft, BuyNft, CollectionOffset, CollectionOffsetBid, SaleHistoryOffset, SaleHistoryOffsetByUser, AskPriceOffset, SaleHistoryTimeOffset, SortOrder, AskSetting, UpdateAskMsg, MakeCounterOfferMsg, BuyCw1155Msg, SaleHistoryOffsetByToken, SaleHistoryTokenOffset, AskExpiryOffset, CollectionBidExpiryOffset, MarketplaceHookMsg, AskHookMsg, SaleHookMsg, OfferHookMsg, Cw1155AskHookMsg, Cw1155BidHookMsg, TokenHookMsg, NftHookMsg, ReceiveMsg, Cw1155HookMsg, PendingRewardsResponse};
+use crate::query::{query_ask_count, query_asks_by_seller, query_bids_by_bidder, query_state_info, query_ask, query_asks, query_bids, query_bids_by_seller, query_bids_by_bidder_sorted_by_expiry, query_tvl_by_collection, query_tvl_by_denom, query_sale_history, query_sale_history_by_token_id, query_sale_history_by_buyer, query_sale_history_by_seller, query_collection_bid, query_collection_bids_by_bidder, query_collection_bid_by_collection, query_asks_sorted_by_price, reverse_query_asks_sorted_by_price, query_top_bid, query_highest_collection_bid, query_collection_stats, query_candles, query_sale_history_in_range, SaleHistoryRange, query_activities_by_collection, query_activities_by_token, query_activities_by_user, query_hooks, query_reward_program, query_pending_rewards, query_finder_earnings, query_cleanup_bounty, query_asks_by_expiry, query_bids_by_expiry, query_collection_bids_by_expiry, query_counter_offer, query_counter_offers_by_bidder, query_counter_offers_by_seller, query_offer, query_offers_by_token, query_offers_by_bidder, query_cw1155_asks, query_cw1155_bids};
+use crate::state::{ask_key, asks, bid_key, bids, collection_bid_key, collection_bids, Ask, Bid, SaleType, Asset, UserInfo, Candle, CandleInterval, sale_history, legacy_sale_history, SaleInfo, Activity, ActivityType, HookType, NftStandard, CLEANUP_GRACE_SECONDS};
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{coin, coins, Addr, DepsMut, MessageInfo, Timestamp, Uint128,to_binary, to_json_binary, Env, Decimal, CosmosMsg, WasmMsg, StdResult, Response, Coin, BankMsg, Event, SubMsg, ReplyOn, Reply, SubMsgResult, WasmQuery, SystemResult, SystemError, ContractResult, from_json, IbcTimeout, Binary};
 use cw721::{Cw721ReceiveMsg,Cw721ExecuteMsg};
 use cw20::{Cw20ReceiveMsg, Cw20ExecuteMsg};
+use cw_storage_plus::Map;
+use crate::package::{Cw1155ReceiveMsg, Cw1155ExecuteMsg, Ics721QueryMsg, Ics721OutgoingMsg};
 
 fn setup_contract(deps: DepsMut){
    let instantiate_msg = InstantiateMsg {
@@ -60,7 +63,12 @@ fn sell_nft(deps: DepsMut, env: Env,collection:&str, sender: String, denom: Stri
       amount
     },
     expire:Timestamp::from_seconds(env.block.time.seconds() + 300),
-    token_address
+    token_address,
+    instant_sell: None,
+    reserved_for: None,
+    buy_now_price: None,
+    reserve_price: None,
+    min_bid_increment: None
   };
 
   let info = mock_info(collection, &[]);
@@ -77,15 +85,17 @@ fn bid_nft_with_token(deps: DepsMut,env: Env, collection: String, token_id: Opti
      nft_address: collection,
      expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
      sale_type: SaleType::Auction,
-     token_id
+     token_id,
+     finder: None,
+     origin_receiver: None
    };
 
    let info = mock_info(token_address,&[]);
-   let msg = ExecuteMsg::Receive(Cw20ReceiveMsg{
+   let msg = ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{
       sender,
       amount,
       msg: to_binary(&bid_msg).unwrap()
-    });
+    }));
 
    let res = execute(deps, env, info, msg).unwrap();
    Ok(res)
@@ -97,15 +107,17 @@ fn bid_nft_with_token_fixed_price(deps: DepsMut,env: Env, collection: String, to
      nft_address: collection,
      expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
      sale_type: SaleType::FixedPrice,
-     token_id
+     token_id,
+     finder: None,
+     origin_receiver: None
    };
 
    let info = mock_info(token_address,&[]);
-   let msg = ExecuteMsg::Receive(Cw20ReceiveMsg{
+   let msg = ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{
       sender,
       amount,
       msg: to_binary(&bid_msg).unwrap()
-    });
+    }));
 
    let res = execute(deps, env, info, msg).unwrap();
    Ok(res)
@@ -117,15 +129,17 @@ fn collection_bid_nft_with_token(deps: DepsMut,env: Env, collection: String, tok
      nft_address: collection,
      expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
      sale_type: SaleType::CollectionBid,
-     token_id
+     token_id,
+     finder: None,
+     origin_receiver: None
    };
 
    let info = mock_info(token_address,&[]);
-   let msg = ExecuteMsg::Receive(Cw20ReceiveMsg{
+   let msg = ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{
       sender,
       amount,
       msg: to_binary(&bid_msg).unwrap()
-    });
+    }));
 
    let res = execute(deps, env, info, msg).unwrap();
    Ok(res)
@@ -144,7 +158,9 @@ fn bid_nft_with_coin(deps: DepsMut,env: Env, collection: String, token_id: Optio
     expire: Timestamp::from_seconds(env.block.time.seconds() + 300), 
     sale_type: SaleType::Auction, 
     token_id, 
-    list_price: Asset { denom, amount } 
+    list_price: Asset { denom, amount },
+    finder: None,
+    origin_receiver: None
    };
 
    let res = execute(deps, env, info, msg).unwrap();
@@ -163,7 +179,9 @@ fn bid_nft_with_coin_fixed_price(deps: DepsMut,env: Env, collection: String, tok
     expire: Timestamp::from_seconds(env.block.time.seconds() + 300), 
     sale_type: SaleType::FixedPrice, 
     token_id, 
-    list_price: Asset { denom, amount } 
+    list_price: Asset { denom, amount },
+    finder: None,
+    origin_receiver: None
    };
 
    let res = execute(deps, env, info, msg).unwrap();
@@ -182,7 +200,9 @@ fn collection_bid_nft_with_coin(deps: DepsMut,env: Env, collection: String, toke
     expire: Timestamp::from_seconds(env.block.time.seconds() + 300), 
     sale_type: SaleType::CollectionBid, 
     token_id, 
-    list_price: Asset { denom, amount } 
+    list_price: Asset { denom, amount },
+    finder: None,
+    origin_receiver: None
    };
 
    let res = execute(deps, env, info, msg).unwrap();
@@ -713,7 +733,8 @@ fn accept_bid() {
   let  sale_history = query_sale_history_by_seller(deps.as_ref(), "seller1".to_string(), Some(SaleHistoryOffsetByUser{
     collection:"collection1".to_string(),
     token_id:"Hope.1".to_string(),
-    time:1571797419
+    time:1571797419,
+    seq: None
   }), Some(20)).unwrap();
   println!("{:?}", sale_history);
 
@@ -924,6 +945,7 @@ fn fixed_price_buy(){
     collection: "collection1".to_string(),
     token_id: "Hope.1".to_string(),
     time: 1571797419,
+    seq: None,
   }), Some(20)).unwrap();
   println!("{:?}",sale_history);
 
@@ -1205,4 +1227,2189 @@ fn accept_collection_bid(){
 
   let sale_history = query_sale_history(deps.as_ref(), "collection1".to_string(), None, Some(20)).unwrap();
   println!("{:?}",sale_history)
-}
\ No newline at end of file
+}
+
+#[test]
+fn instant_sell_to_collection_bid(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  collection_bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), None, "collection_bider1", "ujuno".to_string(), Uint128::new(3000)).unwrap();
+  collection_bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), None, "collection_bider2", "ujuno".to_string(), Uint128::new(5000)).unwrap();
+  collection_bid_nft_with_token(deps.as_mut(), env.clone(), "collection1".to_string(), None, "hope_address", "collection_bider3".to_string(), Uint128::new(9000)).unwrap();
+
+  //no collection bid reaches the list price, so the ask is stored
+  let sell_msg = SellNft{
+    list_price: Asset{ denom: "ujuno".to_string(), amount: Uint128::new(6000) },
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
+    token_address: None,
+    instant_sell: Some(true),
+    reserved_for: None,
+    buy_now_price: None,
+    reserve_price: None,
+    min_bid_increment: None
+  };
+  let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{
+    sender: "seller1".to_string(),
+    token_id: "Hope.1".to_string(),
+    msg: to_json_binary(&sell_msg).unwrap()
+  });
+  let res = execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), msg).unwrap();
+  assert_eq!(res.messages.len(), 0);
+  assert!(query_ask(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string()).unwrap().ask.is_some());
+
+  //the best coin bid is filled at its own price
+  let sell_msg = SellNft{
+    list_price: Asset{ denom: "ujuno".to_string(), amount: Uint128::new(4000) },
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
+    token_address: None,
+    instant_sell: Some(true),
+    reserved_for: None,
+    buy_now_price: None,
+    reserve_price: None,
+    min_bid_increment: None
+  };
+  let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{
+    sender: "seller1".to_string(),
+    token_id: "Hope.2".to_string(),
+    msg: to_json_binary(&sell_msg).unwrap()
+  });
+  let res = execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), msg).unwrap();
+  assert_eq!(res.messages.len(), 4);
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send {
+    to_address: "admin1".to_string(),
+    amount: vec![Coin{ denom: "ujuno".to_string(), amount: Uint128::new(350) }] }));
+  assert_eq!(res.messages[1].msg, CosmosMsg::Bank(BankMsg::Send {
+    to_address: "admin2".to_string(),
+    amount: vec![Coin{ denom: "ujuno".to_string(), amount: Uint128::new(150) }] }));
+  assert_eq!(res.messages[2].msg, CosmosMsg::Bank(BankMsg::Send {
+    to_address: "seller1".to_string(),
+    amount: vec![Coin{ denom: "ujuno".to_string(), amount: Uint128::new(4500) }] }));
+  assert_eq!(res.messages[3].msg, CosmosMsg::Wasm(WasmMsg::Execute {
+    contract_addr: "collection1".to_string(),
+    msg: to_json_binary(&Cw721ExecuteMsg::TransferNft{ recipient: "collection_bider2".to_string(), token_id: "Hope.2".to_string() }).unwrap(),
+    funds: vec![] }));
+
+  assert!(query_ask(deps.as_ref(), "collection1".to_string(), "Hope.2".to_string()).unwrap().ask.is_none());
+  assert!(query_collection_bid(deps.as_ref(), "collection1".to_string(), "collection_bider2".to_string()).unwrap().bid.is_none());
+
+  //without the flag the ask is listed even though a matching bid exists
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.3".to_string());
+  assert!(query_ask(deps.as_ref(), "collection1".to_string(), "Hope.3".to_string()).unwrap().ask.is_some());
+  assert!(query_collection_bid(deps.as_ref(), "collection1".to_string(), "collection_bider1".to_string()).unwrap().bid.is_some());
+
+  //the seller's own collection bid is skipped
+  let sell_msg = SellNft{
+    list_price: Asset{ denom: "ujuno".to_string(), amount: Uint128::new(2000) },
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
+    token_address: None,
+    instant_sell: Some(true),
+    reserved_for: None,
+    buy_now_price: None,
+    reserve_price: None,
+    min_bid_increment: None
+  };
+  let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{
+    sender: "collection_bider1".to_string(),
+    token_id: "Hope.4".to_string(),
+    msg: to_json_binary(&sell_msg).unwrap()
+  });
+  let res = execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), msg).unwrap();
+  assert_eq!(res.messages.len(), 0);
+  assert!(query_ask(deps.as_ref(), "collection1".to_string(), "Hope.4".to_string()).unwrap().ask.is_some());
+  assert!(query_collection_bid(deps.as_ref(), "collection1".to_string(), "collection_bider1".to_string()).unwrap().bid.is_some());
+}
+
+
+#[test]
+fn price_sorted_queries(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(30000), None, "Hope.1".to_string());
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(10000), None, "Hope.2".to_string());
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller2".to_string(), "ujuno".to_string(), Uint128::new(20000), None, "Hope.3".to_string());
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller2".to_string(), "hope".to_string(), Uint128::new(500), Some("hope_address".to_string()), "Hope.4".to_string());
+
+  let asks = query_asks_sorted_by_price(deps.as_ref(), "collection1".to_string(), "ujuno".to_string(), None, None).unwrap().asks;
+  let token_ids: Vec<String> = asks.iter().map(|ask| ask.token_id.clone()).collect();
+  assert_eq!(token_ids, vec!["Hope.2", "Hope.3", "Hope.1"]);
+
+  let asks = query_asks_sorted_by_price(deps.as_ref(), "collection1".to_string(), "ujuno".to_string(), Some(AskPriceOffset{
+    price: Uint128::new(10000),
+    token_id: "Hope.2".to_string()
+  }), Some(1)).unwrap().asks;
+  assert_eq!(asks[0].token_id, "Hope.3".to_string());
+
+  let asks = reverse_query_asks_sorted_by_price(deps.as_ref(), "collection1".to_string(), "ujuno".to_string(), None, None).unwrap().asks;
+  let token_ids: Vec<String> = asks.iter().map(|ask| ask.token_id.clone()).collect();
+  assert_eq!(token_ids, vec!["Hope.1", "Hope.3", "Hope.2"]);
+
+  //price updates move the ask in the index
+  let info = mock_info("seller1", &[]);
+  let msg = ExecuteMsg::UpdateAskPrice {
+    nft_address: "collection1".to_string(),
+    token_id: "Hope.1".to_string(),
+    list_price: Asset{ denom: "ujuno".to_string(), amount: Uint128::new(5000) },
+    token_address: None
+  };
+  execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+  let asks = query_asks_sorted_by_price(deps.as_ref(), "collection1".to_string(), "ujuno".to_string(), None, Some(1)).unwrap().asks;
+  assert_eq!(asks[0].token_id, "Hope.1".to_string());
+
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.3".to_string()), "bider1", "ujuno".to_string(), Uint128::new(7000)).unwrap();
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.3".to_string()), "bider2", "ujuno".to_string(), Uint128::new(9000)).unwrap();
+  bid_nft_with_token(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.3".to_string()), "hope_address", "bider3".to_string(), Uint128::new(100000)).unwrap();
+
+  let top_bid = query_top_bid(deps.as_ref(), env.clone(), "collection1".to_string(), "Hope.3".to_string(), "ujuno".to_string()).unwrap().bid.unwrap();
+  assert_eq!(top_bid.bidder, "bider2".to_string());
+  let top_bid = query_top_bid(deps.as_ref(), env.clone(), "collection1".to_string(), "Hope.3".to_string(), "hope".to_string()).unwrap().bid.unwrap();
+  assert_eq!(top_bid.bidder, "bider3".to_string());
+  assert!(query_top_bid(deps.as_ref(), env.clone(), "collection1".to_string(), "Hope.1".to_string(), "ujuno".to_string()).unwrap().bid.is_none());
+
+  collection_bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), None, "collection_bider1", "ujuno".to_string(), Uint128::new(3000)).unwrap();
+  collection_bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), None, "collection_bider2", "ujuno".to_string(), Uint128::new(4000)).unwrap();
+
+  let highest = query_highest_collection_bid(deps.as_ref(), env.clone(), "collection1".to_string(), "ujuno".to_string()).unwrap().bid.unwrap();
+  assert_eq!(highest.bidder, "collection_bider2".to_string());
+
+  //expired bids are skipped
+  let mut later = env.clone();
+  later.block.time = later.block.time.plus_seconds(301);
+  assert!(query_highest_collection_bid(deps.as_ref(), later.clone(), "collection1".to_string(), "ujuno".to_string()).unwrap().bid.is_none());
+  assert!(query_top_bid(deps.as_ref(), later, "collection1".to_string(), "Hope.3".to_string(), "ujuno".to_string()).unwrap().bid.is_none());
+}
+
+
+#[test]
+fn collection_stats(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+
+  let stats = query_collection_stats(deps.as_ref(), env.clone(), "collection1".to_string(), "ujuno".to_string()).unwrap();
+  assert_eq!(stats.sale_count, 0);
+  assert_eq!(stats.floor_price, None);
+  assert_eq!(stats.last_sale_price, None);
+
+  //a sale 10 days ago
+  let mut old_env = env.clone();
+  old_env.block.time = env.block.time.minus_seconds(10 * 86400);
+  sell_nft(deps.as_mut(), old_env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(50000), None, "Hope.1".to_string());
+  bid_nft_with_coin_fixed_price(deps.as_mut(), old_env, "collection1".to_string(), Some("Hope.1".to_string()), "buyer1", "ujuno".to_string(), Uint128::new(50000)).unwrap();
+
+  //a sale 3 days ago
+  let mut old_env = env.clone();
+  old_env.block.time = env.block.time.minus_seconds(3 * 86400);
+  sell_nft(deps.as_mut(), old_env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(20000), None, "Hope.2".to_string());
+  bid_nft_with_coin_fixed_price(deps.as_mut(), old_env, "collection1".to_string(), Some("Hope.2".to_string()), "buyer1", "ujuno".to_string(), Uint128::new(20000)).unwrap();
+
+  //two sales now
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(10000), None, "Hope.3".to_string());
+  bid_nft_with_coin_fixed_price(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.3".to_string()), "buyer1", "ujuno".to_string(), Uint128::new(10000)).unwrap();
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(15000), None, "Hope.4".to_string());
+  bid_nft_with_coin_fixed_price(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.4".to_string()), "buyer1", "ujuno".to_string(), Uint128::new(15000)).unwrap();
+
+  //active asks set the floor
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(12000), None, "Hope.5".to_string());
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(8000), None, "Hope.6".to_string());
+
+  let stats = query_collection_stats(deps.as_ref(), env.clone(), "collection1".to_string(), "ujuno".to_string()).unwrap();
+  assert_eq!(stats.sale_count, 4);
+  assert_eq!(stats.last_sale_price, Some(Uint128::new(15000)));
+  assert_eq!(stats.last_sale_time, Some(env.block.time.seconds()));
+  assert_eq!(stats.all_time_high, Some(Uint128::new(50000)));
+  assert_eq!(stats.total_volume, Uint128::new(95000));
+  assert_eq!(stats.volume_24h, Uint128::new(25000));
+  assert_eq!(stats.volume_7d, Uint128::new(45000));
+  assert_eq!(stats.volume_30d, Uint128::new(95000));
+  assert_eq!(stats.floor_price, Some(Uint128::new(8000)));
+
+  //a day and a minute later the sales have left the 24h window
+  let mut next_day = env.clone();
+  next_day.block.time = env.block.time.plus_seconds(86400 + 60);
+  let stats = query_collection_stats(deps.as_ref(), next_day, "collection1".to_string(), "ujuno".to_string()).unwrap();
+  assert_eq!(stats.volume_24h, Uint128::zero());
+
+  //once the asks expire there is no floor and the windows roll forward
+  let mut later = env.clone();
+  later.block.time = env.block.time.plus_seconds(2 * 86400);
+  let stats = query_collection_stats(deps.as_ref(), later, "collection1".to_string(), "ujuno".to_string()).unwrap();
+  assert_eq!(stats.floor_price, None);
+  assert_eq!(stats.volume_24h, Uint128::zero());
+  assert_eq!(stats.volume_7d, Uint128::new(45000));
+}
+
+
+#[test]
+fn price_candles(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+
+  let sales = vec![(0, 10000), (60, 30000), (120, 5000), (180, 20000), (3600, 7000)];
+  for (index, (offset, price)) in sales.into_iter().enumerate() {
+    let mut sale_env = env.clone();
+    sale_env.block.time = env.block.time.plus_seconds(offset);
+    let token_id = format!("Hope.{}", index);
+    sell_nft(deps.as_mut(), sale_env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(price), None, token_id.clone());
+    bid_nft_with_coin_fixed_price(deps.as_mut(), sale_env, "collection1".to_string(), Some(token_id), "buyer1", "ujuno".to_string(), Uint128::new(price)).unwrap();
+  }
+
+  let hour_start = env.block.time.seconds() - env.block.time.seconds() % 3600;
+  let candles = query_candles(deps.as_ref(), "collection1".to_string(), "ujuno".to_string(), CandleInterval::Hour, None, None, None).unwrap().candles;
+  assert_eq!(candles, vec![
+    Candle{
+      start_time: hour_start,
+      open: Uint128::new(10000),
+      high: Uint128::new(30000),
+      low: Uint128::new(5000),
+      close: Uint128::new(20000),
+      volume: Uint128::new(65000),
+      sale_count: 4
+    },
+    Candle{
+      start_time: hour_start + 3600,
+      open: Uint128::new(7000),
+      high: Uint128::new(7000),
+      low: Uint128::new(7000),
+      close: Uint128::new(7000),
+      volume: Uint128::new(7000),
+      sale_count: 1
+    }
+  ]);
+
+  //time range and pagination
+  let candles = query_candles(deps.as_ref(), "collection1".to_string(), "ujuno".to_string(), CandleInterval::Hour, Some(hour_start + 1), None, None).unwrap().candles;
+  assert_eq!(candles.len(), 1);
+  assert_eq!(candles[0].start_time, hour_start + 3600);
+  let candles = query_candles(deps.as_ref(), "collection1".to_string(), "ujuno".to_string(), CandleInterval::Hour, None, Some(hour_start + 3600), None).unwrap().candles;
+  assert_eq!(candles.len(), 1);
+  assert_eq!(candles[0].start_time, hour_start);
+
+  let candles = query_candles(deps.as_ref(), "collection1".to_string(), "ujuno".to_string(), CandleInterval::Day, None, None, None).unwrap().candles;
+  assert_eq!(candles.len(), 1);
+  assert_eq!(candles[0].start_time % 86400, 0);
+  assert_eq!(candles[0].open, Uint128::new(10000));
+  assert_eq!(candles[0].close, Uint128::new(7000));
+  assert_eq!(candles[0].volume, Uint128::new(72000));
+  assert_eq!(candles[0].sale_count, 5);
+}
+
+
+#[test]
+fn sale_history_time_range(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  let start = env.block.time.seconds();
+  for index in 0..5u64 {
+    let mut sale_env = env.clone();
+    sale_env.block.time = env.block.time.plus_seconds(index * 100);
+    let token_id = format!("Hope.{}", index);
+    let buyer = if index % 2 == 0 { "buyer1" } else { "buyer2" };
+    if index == 3 {
+      sell_nft(deps.as_mut(), sale_env.clone(), "collection1", "seller1".to_string(), "hope".to_string(), Uint128::new(1000), Some("hope_address".to_string()), token_id.clone());
+      bid_nft_with_token_fixed_price(deps.as_mut(), sale_env, "collection1".to_string(), Some(token_id), "hope_address", buyer.to_string(), Uint128::new(1000)).unwrap();
+    } else {
+      sell_nft(deps.as_mut(), sale_env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, token_id.clone());
+      bid_nft_with_coin_fixed_price(deps.as_mut(), sale_env, "collection1".to_string(), Some(token_id), buyer, "ujuno".to_string(), Uint128::new(1000)).unwrap();
+    }
+  }
+
+  let range = |start_time: Option<u64>, end_time: Option<u64>, denom: Option<&str>, start_after: Option<SaleHistoryTimeOffset>| SaleHistoryRange {
+    start_time,
+    end_time,
+    denom: denom.map(|denom| denom.to_string()),
+    start_after
+  };
+  let token_ids = |sales: Vec<crate::state::SaleInfo>| sales.into_iter().map(|sale| sale.token_id).collect::<Vec<String>>();
+
+  //sales between t1 and t2
+  let res = query_sale_history_in_range(deps.as_ref(), sale_history().idx.collection_time, sale_history().idx.collection_denom_time, "collection1".to_string(), range(Some(start + 100), Some(start + 400), None, None), None, None).unwrap();
+  assert_eq!(token_ids(res.sale_history), vec!["Hope.1", "Hope.2", "Hope.3"]);
+
+  //descending with pagination
+  let res = query_sale_history_in_range(deps.as_ref(), sale_history().idx.collection_time, sale_history().idx.collection_denom_time, "collection1".to_string(), range(None, None, None, None), Some(2), Some(SortOrder::Descending)).unwrap();
+  assert_eq!(token_ids(res.sale_history.clone()), vec!["Hope.4", "Hope.3"]);
+  let last = res.sale_history.last().unwrap().clone();
+  let res = query_sale_history_in_range(deps.as_ref(), sale_history().idx.collection_time, sale_history().idx.collection_denom_time, "collection1".to_string(), range(None, None, None, Some(SaleHistoryTimeOffset{
+    collection: last.collection,
+    token_id: last.token_id,
+    time: last.time,
+    seq: last.seq
+  })), Some(2), Some(SortOrder::Descending)).unwrap();
+  assert_eq!(token_ids(res.sale_history), vec!["Hope.2", "Hope.1"]);
+
+  //denom filter
+  let res = query_sale_history_in_range(deps.as_ref(), sale_history().idx.collection_time, sale_history().idx.collection_denom_time, "collection1".to_string(), range(None, None, Some("hope"), None), None, None).unwrap();
+  assert_eq!(token_ids(res.sale_history), vec!["Hope.3"]);
+
+  //by user
+  let res = query_sale_history_in_range(deps.as_ref(), sale_history().idx.buyer_time, sale_history().idx.buyer_denom_time, "buyer1".to_string(), range(Some(start + 1), None, None, None), None, Some(SortOrder::Descending)).unwrap();
+  assert_eq!(token_ids(res.sale_history), vec!["Hope.4", "Hope.2"]);
+  let res = query_sale_history_in_range(deps.as_ref(), sale_history().idx.seller_time, sale_history().idx.seller_denom_time, "seller1".to_string(), range(None, Some(start + 200), Some("ujuno"), None), None, None).unwrap();
+  assert_eq!(token_ids(res.sale_history), vec!["Hope.0", "Hope.1"]);
+}
+
+#[test]
+fn sale_history_sequence(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+
+  //the same token sold twice in one block keeps both entries
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.1".to_string());
+  bid_nft_with_coin_fixed_price(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "buyer1", "ujuno".to_string(), Uint128::new(1000)).unwrap();
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "buyer1".to_string(), "ujuno".to_string(), Uint128::new(2000), None, "Hope.1".to_string());
+  bid_nft_with_coin_fixed_price(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "buyer2", "ujuno".to_string(), Uint128::new(2000)).unwrap();
+
+  let res = query_sale_history_by_token_id(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap();
+  assert_eq!(res.sale_history.len(), 2);
+  assert_eq!(res.sale_history[0].seq, 1);
+  assert_eq!(res.sale_history[0].to, "buyer1");
+  assert_eq!(res.sale_history[1].seq, 2);
+  assert_eq!(res.sale_history[1].to, "buyer2");
+
+  //paginate on (time, seq)
+  let res = query_sale_history_by_token_id(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), Some(SaleHistoryTokenOffset::Sale(SaleHistoryOffsetByToken{
+    time: env.block.time.seconds(),
+    seq: 1
+  })), None).unwrap();
+  assert_eq!(res.sale_history.len(), 1);
+  assert_eq!(res.sale_history[0].to, "buyer2");
+
+  //offsets from before sequence numbers still parse, a bare time skips every sale at that time
+  let start_after: SaleHistoryTokenOffset = from_json(to_json_binary(&env.block.time.seconds()).unwrap()).unwrap();
+  let res = query_sale_history_by_token_id(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), Some(start_after), None).unwrap();
+  assert!(res.sale_history.is_empty());
+  let offset: SaleHistoryOffsetByUser = from_json(Binary::from(br#"{"collection":"collection1","token_id":"Hope.1","time":1}"#.to_vec())).unwrap();
+  assert_eq!(offset.seq, None);
+
+  //entries stored before sequence numbers are moved over by the owner
+  let legacy_sale = |token_id: &str, time: u64| SaleInfo {
+    from: "seller2".to_string(),
+    to: "buyer3".to_string(),
+    denom: "ujuno".to_string(),
+    amount: Uint128::new(500),
+    time,
+    collection: "collection1".to_string(),
+    token_id: token_id.to_string(),
+    seq: 0,
+    quantity: None
+  };
+  legacy_sale_history().save(deps.as_mut().storage, ("collection1".to_string(), "Hope.2".to_string(), 100), &legacy_sale("Hope.2", 100)).unwrap();
+  legacy_sale_history().save(deps.as_mut().storage, ("collection1".to_string(), "Hope.3".to_string(), 200), &legacy_sale("Hope.3", 200)).unwrap();
+
+  let info = mock_info("buyer1", &[]);
+  let msg = ExecuteMsg::MigrateSaleHistory { limit: None };
+  match execute(deps.as_mut(), env.clone(), info, msg) {
+    Err(ContractError::Unauthorized {}) => {}
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let info = mock_info("owner", &[]);
+  let msg = ExecuteMsg::MigrateSaleHistory { limit: Some(1) };
+  let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+  assert_eq!(res.attributes[1].value, "1");
+  let msg = ExecuteMsg::MigrateSaleHistory { limit: None };
+  let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+  assert_eq!(res.attributes[1].value, "1");
+
+  let legacy = legacy_sale_history().range(deps.as_ref().storage, None, None, cosmwasm_std::Order::Ascending).count();
+  assert_eq!(legacy, 0);
+
+  let res = query_sale_history_by_buyer(deps.as_ref(), "buyer3".to_string(), None, None).unwrap();
+  assert_eq!(res.sale_history.iter().map(|sale| (sale.token_id.clone(), sale.seq)).collect::<Vec<_>>(), vec![("Hope.2".to_string(), 3), ("Hope.3".to_string(), 4)]);
+}
+
+#[test]
+fn activity_log(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.1".to_string());
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bider1", "ujuno".to_string(), Uint128::new(500)).unwrap();
+
+  let info = mock_info("seller1", &[]);
+  let msg = ExecuteMsg::UpdateAskPrice { nft_address: "collection1".to_string(), token_id: "Hope.1".to_string(), list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(800) }, token_address: None };
+  execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+  let info = mock_info("bider1", &[]);
+  let msg = ExecuteMsg::RemoveBid { nft_address: "collection1".to_string(), token_id: "Hope.1".to_string() };
+  execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+  collection_bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), None, "buyer2", "ujuno".to_string(), Uint128::new(300)).unwrap();
+  bid_nft_with_coin_fixed_price(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "buyer1", "ujuno".to_string(), Uint128::new(800)).unwrap();
+
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.2".to_string());
+  let info = mock_info("seller1", &[]);
+  let msg = ExecuteMsg::WithdrawNft { nft_address: "collection1".to_string(), token_id: "Hope.2".to_string() };
+  execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+  let types = |activities: Vec<Activity>| activities.into_iter().map(|activity| activity.activity_type).collect::<Vec<ActivityType>>();
+
+  let res = query_activities_by_collection(deps.as_ref(), "collection1".to_string(), None, None).unwrap();
+  assert_eq!(types(res.activities), vec![
+    ActivityType::Cancellation,
+    ActivityType::Listing,
+    ActivityType::Sale,
+    ActivityType::CollectionBid,
+    ActivityType::BidRemoval,
+    ActivityType::PriceUpdate,
+    ActivityType::Bid,
+    ActivityType::Listing
+  ]);
+
+  let res = query_activities_by_token(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, Some(2)).unwrap();
+  assert_eq!(res.activities[0], Activity {
+    id: 6,
+    activity_type: ActivityType::Sale,
+    actor: "buyer1".to_string(),
+    counterparty: Some("seller1".to_string()),
+    collection: "collection1".to_string(),
+    token_id: Some("Hope.1".to_string()),
+    price: Some(Asset { denom: "ujuno".to_string(), amount: Uint128::new(800) }),
+    time: env.block.time.seconds()
+  });
+  let res = query_activities_by_token(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), Some(res.activities[1].id), None).unwrap();
+  assert_eq!(types(res.activities), vec![ActivityType::PriceUpdate, ActivityType::Bid, ActivityType::Listing]);
+
+  //the seller sees their own actions and the bids and sales they were counterparty to
+  let res = query_activities_by_user(deps.as_ref(), "seller1".to_string(), None, None).unwrap();
+  assert_eq!(res.activities.iter().map(|activity| activity.id).collect::<Vec<u64>>(), vec![8, 7, 6, 4, 3, 2, 1]);
+  let res = query_activities_by_user(deps.as_ref(), "buyer2".to_string(), None, None).unwrap();
+  assert_eq!(types(res.activities), vec![ActivityType::CollectionBid]);
+}
+
+#[test]
+fn typed_events(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  let info = mock_info("owner", &[]);
+  let msg = ExecuteMsg::AddCoin { symbol: "ujuno".to_string() };
+  let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+  assert_eq!(res.events, vec![Event::new("coin-added").add_attribute("denom", "ujuno")]);
+
+  let expire = Timestamp::from_seconds(env.block.time.seconds() + 300);
+  let sell_msg = SellNft{
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(1000) },
+    expire,
+    token_address: None,
+    instant_sell: None,
+    reserved_for: None,
+    buy_now_price: None,
+    reserve_price: None,
+    min_bid_increment: None
+  };
+  let info = mock_info("collection1", &[]);
+  let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{
+    sender: "seller1".to_string(),
+    token_id: "Hope.1".to_string(),
+    msg: to_json_binary(&sell_msg).unwrap()
+  });
+  let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+  assert_eq!(res.events, vec![Event::new("ask-created")
+    .add_attribute("collection", "collection1")
+    .add_attribute("token_id", "Hope.1")
+    .add_attribute("seller", "seller1")
+    .add_attribute("denom", "ujuno")
+    .add_attribute("amount", "1000")
+    .add_attribute("expires_at", expire.seconds().to_string())]);
+  //existing attributes are kept
+  assert_eq!(res.attributes[0].value, "Put NFT on Sale");
+
+  let res = bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bider1", "ujuno".to_string(), Uint128::new(500)).unwrap();
+  assert_eq!(res.events, vec![Event::new("bid-placed")
+    .add_attribute("collection", "collection1")
+    .add_attribute("token_id", "Hope.1")
+    .add_attribute("seller", "seller1")
+    .add_attribute("bidder", "bider1")
+    .add_attribute("denom", "ujuno")
+    .add_attribute("amount", "500")
+    .add_attribute("expires_at", expire.seconds().to_string())]);
+
+  let res = bid_nft_with_coin_fixed_price(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "buyer1", "ujuno".to_string(), Uint128::new(1000)).unwrap();
+  assert_eq!(res.events, vec![Event::new("sale")
+    .add_attribute("collection", "collection1")
+    .add_attribute("token_id", "Hope.1")
+    .add_attribute("seller", "seller1")
+    .add_attribute("buyer", "buyer1")
+    .add_attribute("denom", "ujuno")
+    .add_attribute("amount", "1000")
+    .add_attribute("sale_type", "fixed_price")]);
+
+  let info = mock_info("admin", &[]);
+  let msg = ExecuteMsg::ChangeOwner { address: "owner2".to_string() };
+  let res = execute(deps.as_mut(), env, info, msg).unwrap();
+  assert_eq!(res.events, vec![Event::new("owner-changed").add_attribute("owner", "owner2")]);
+}
+
+#[test]
+fn marketplace_hooks(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+
+  let info = mock_info("seller1", &[]);
+  let msg = ExecuteMsg::AddHook { hook_type: HookType::Sale, address: "sale_hook".to_string() };
+  match execute(deps.as_mut(), env.clone(), info, msg) {
+    Err(ContractError::Unauthorized {}) => {}
+    _ => panic!("Must return unauthorized error"),
+  }
+
+  let info = mock_info("owner", &[]);
+  for (hook_type, address) in [(HookType::Sale, "sale_hook"), (HookType::Ask, "ask_hook"), (HookType::Bid, "bid_hook")] {
+    let msg = ExecuteMsg::AddHook { hook_type, address: address.to_string() };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+  }
+  let msg = ExecuteMsg::AddHook { hook_type: HookType::Sale, address: "sale_hook".to_string() };
+  match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
+    Err(ContractError::HookAlreadyRegistered {}) => {}
+    _ => panic!("Must return hook already registered error"),
+  }
+  assert_eq!(query_hooks(deps.as_ref(), HookType::Sale).unwrap().hooks, vec!["sale_hook".to_string()]);
+
+  let sell_msg = SellNft{
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(1000) },
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
+    token_address: None,
+    instant_sell: None,
+    reserved_for: None,
+    buy_now_price: None,
+    reserve_price: None,
+    min_bid_increment: None
+  };
+  let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{
+    sender: "seller1".to_string(),
+    token_id: "Hope.1".to_string(),
+    msg: to_json_binary(&sell_msg).unwrap()
+  });
+  let res = execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), msg).unwrap();
+  let ask = query_ask(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string()).unwrap().ask.unwrap();
+  assert_eq!(res.messages, vec![SubMsg::reply_on_error(
+    MarketplaceHookMsg::AskHook(AskHookMsg { ask }).into_cosmos_msg("ask_hook".to_string()).unwrap(),
+    1
+  ).with_gas_limit(500_000)]);
+
+  let res = bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bider1", "ujuno".to_string(), Uint128::new(500)).unwrap();
+  assert_eq!(res.messages.len(), 1);
+  assert_eq!(res.messages[0].reply_on, ReplyOn::Error);
+
+  let res = bid_nft_with_coin_fixed_price(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "buyer1", "ujuno".to_string(), Uint128::new(1000)).unwrap();
+  let hook_msg = MarketplaceHookMsg::SaleHook(SaleHookMsg {
+    collection: "collection1".to_string(),
+    token_id: "Hope.1".to_string(),
+    seller: "seller1".to_string(),
+    buyer: "buyer1".to_string(),
+    price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(1000) },
+    time: env.block.time.seconds(),
+    quantity: None
+  });
+  //hooks run with a gas limit after the usual refunds, payouts and NFT transfer
+  let (hook, settlement) = res.messages.split_last().unwrap();
+  assert_eq!(*hook, SubMsg::reply_on_error(hook_msg.into_cosmos_msg("sale_hook".to_string()).unwrap(), 1).with_gas_limit(500_000));
+  assert!(settlement.iter().all(|msg| msg.reply_on == ReplyOn::Never));
+
+  //a failing hook does not revert the trade
+  let reply = Reply { id: 1, result: SubMsgResult::Err("hook out of gas".to_string()) };
+  let res = reply_entry(deps.as_mut(), env.clone(), reply).unwrap();
+  assert_eq!(res.attributes[1].value, "hook out of gas");
+  let reply = Reply { id: 7, result: SubMsgResult::Err("unknown".to_string()) };
+  match reply_entry(deps.as_mut(), env.clone(), reply) {
+    Err(ContractError::UnknownReplyId { id: 7 }) => {}
+    _ => panic!("Must return unknown reply id error"),
+  }
+
+  let msg = ExecuteMsg::RemoveHook { hook_type: HookType::Sale, address: "sale_hook".to_string() };
+  execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+  assert!(query_hooks(deps.as_ref(), HookType::Sale).unwrap().hooks.is_empty());
+  let msg = ExecuteMsg::RemoveHook { hook_type: HookType::Sale, address: "sale_hook".to_string() };
+  match execute(deps.as_mut(), env, info, msg) {
+    Err(ContractError::HookNotRegistered {}) => {}
+    _ => panic!("Must return hook not registered error"),
+  }
+}
+
+#[test]
+fn trading_rewards(){
+  let mut deps = mock_dependencies();
+  let mut env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+
+  let fund = |amount: u128| ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{
+    sender: "owner".to_string(),
+    amount: Uint128::new(amount),
+    msg: to_json_binary(&TokenHookMsg::FundRewards {}).unwrap()
+  }));
+
+  //no program yet
+  match execute(deps.as_mut(), env.clone(), mock_info("reward_address", &[]), fund(150)) {
+    Err(ContractError::NoRewardProgram {}) => {}
+    _ => panic!("Must return no reward program error"),
+  }
+
+  let info = mock_info("owner", &[]);
+  let msg = ExecuteMsg::SetRewardConfig { token_address: "reward_address".to_string(), epoch_seconds: 100 };
+  execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+  let msg = ExecuteMsg::SetRewardRate { collection: "collection1".to_string(), denom: "ujuno".to_string(), rate: Decimal::percent(10) };
+  execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+  //only the reward token funds the pool
+  match execute(deps.as_mut(), env.clone(), mock_info("hope_address", &[]), fund(150)) {
+    Err(ContractError::WrongTokenContractError {}) => {}
+    _ => panic!("Must return wrong token error"),
+  }
+  execute(deps.as_mut(), env.clone(), mock_info("reward_address", &[]), fund(150)).unwrap();
+  assert_eq!(query_reward_program(deps.as_ref()).unwrap().pool, Uint128::new(150));
+
+  let msg = ExecuteMsg::SetRewardConfig { token_address: "other_reward".to_string(), epoch_seconds: 100 };
+  match execute(deps.as_mut(), env.clone(), info, msg) {
+    Err(ContractError::RewardPoolNotEmpty {}) => {}
+    _ => panic!("Must return reward pool not empty error"),
+  }
+
+  //10% of 1000 for the buyer, the rest of the pool for the seller
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.1".to_string());
+  bid_nft_with_coin_fixed_price(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "buyer1", "ujuno".to_string(), Uint128::new(1000)).unwrap();
+  assert_eq!(query_reward_program(deps.as_ref()).unwrap().pool, Uint128::zero());
+
+  let pending = query_pending_rewards(deps.as_ref(), env.clone(), "buyer1".to_string()).unwrap();
+  assert_eq!(pending, PendingRewardsResponse { claimable: Uint128::zero(), accruing: Uint128::new(100) });
+  let pending = query_pending_rewards(deps.as_ref(), env.clone(), "seller1".to_string()).unwrap();
+  assert_eq!(pending.accruing, Uint128::new(50));
+
+  //rewards unlock when the epoch ends
+  match execute(deps.as_mut(), env.clone(), mock_info("buyer1", &[]), ExecuteMsg::ClaimRewards {}) {
+    Err(ContractError::NothingToClaim {}) => {}
+    _ => panic!("Must return nothing to claim error"),
+  }
+  env.block.time = env.block.time.plus_seconds(100);
+  let pending = query_pending_rewards(deps.as_ref(), env.clone(), "buyer1".to_string()).unwrap();
+  assert_eq!(pending, PendingRewardsResponse { claimable: Uint128::new(100), accruing: Uint128::zero() });
+
+  let res = execute(deps.as_mut(), env.clone(), mock_info("buyer1", &[]), ExecuteMsg::ClaimRewards {}).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "reward_address".to_string(),
+    msg: to_json_binary(&Cw20ExecuteMsg::Transfer{ recipient: "buyer1".to_string(), amount: Uint128::new(100) }).unwrap(),
+    funds: vec![] }));
+  let pending = query_pending_rewards(deps.as_ref(), env.clone(), "buyer1".to_string()).unwrap();
+  assert_eq!(pending.claimable, Uint128::zero());
+
+  //the token can not change while accrued rewards are still owed in it
+  let msg = ExecuteMsg::SetRewardConfig { token_address: "other_reward".to_string(), epoch_seconds: 100 };
+  match execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg.clone()) {
+    Err(ContractError::UnclaimedRewards {}) => {}
+    _ => panic!("Must return unclaimed rewards error"),
+  }
+  execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), ExecuteMsg::ClaimRewards {}).unwrap();
+  execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+  //selling to yourself earns nothing
+  execute(deps.as_mut(), env.clone(), mock_info("other_reward", &[]), fund(150)).unwrap();
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.2".to_string());
+  bid_nft_with_coin_fixed_price(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.2".to_string()), "seller1", "ujuno".to_string(), Uint128::new(1000)).unwrap();
+  assert_eq!(query_reward_program(deps.as_ref()).unwrap().pool, Uint128::new(150));
+  assert_eq!(query_pending_rewards(deps.as_ref(), env, "seller1".to_string()).unwrap().accruing, Uint128::zero());
+}
+
+#[test]
+fn finder_fee(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  let msg = ExecuteMsg::SetFinderFee { nft_address: "collection1".to_string(), finder_fee: Decimal::percent(10) };
+  match execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), msg.clone()) {
+    Err(ContractError::Unauthorized {}) => {}
+    _ => panic!("Must return unauthorized error"),
+  }
+  let too_much = ExecuteMsg::SetFinderFee { nft_address: "collection1".to_string(), finder_fee: Decimal::percent(101) };
+  match execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), too_much) {
+    Err(ContractError::WrongPortionError {}) => {}
+    _ => panic!("Must return wrong portion error"),
+  }
+  execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+  //fixed price purchase with coin: 10% of the 900 seller proceeds go to the finder
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.1".to_string());
+  let info = mock_info("buyer1", &[coin(1000, "ujuno")]);
+  let msg = ExecuteMsg::SetBidCoin {
+    nft_address: "collection1".to_string(),
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
+    sale_type: SaleType::FixedPrice,
+    token_id: Some("Hope.1".to_string()),
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(1000) },
+    finder: Some("finder1".to_string()),
+    origin_receiver: None
+  };
+  let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+  assert_eq!(res.messages[2].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "finder1".to_string(), amount: coins(90, "ujuno") }));
+  assert_eq!(res.messages[3].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "seller1".to_string(), amount: coins(810, "ujuno") }));
+
+  //the finder of an auction bid in tokens is paid when the bid is accepted
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "hope".to_string(), Uint128::new(1000), Some("hope_address".to_string()), "Hope.2".to_string());
+  let bid_msg = BuyNft{
+    nft_address: "collection1".to_string(),
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
+    sale_type: SaleType::Auction,
+    token_id: Some("Hope.2".to_string()),
+    finder: Some("finder1".to_string()),
+    origin_receiver: None
+  };
+  let msg = ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{ sender: "bider1".to_string(), amount: Uint128::new(500), msg: to_json_binary(&bid_msg).unwrap() }));
+  execute(deps.as_mut(), env.clone(), mock_info("hope_address", &[]), msg).unwrap();
+
+  let msg = ExecuteMsg::AcceptBid { nft_address: "collection1".to_string(), token_id: "Hope.2".to_string(), bidder: "bider1".to_string() };
+  let res = execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), msg).unwrap();
+  assert_eq!(res.messages[2].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "hope_address".to_string(),
+    msg: to_json_binary(&Cw20ExecuteMsg::Transfer{ recipient: "finder1".to_string(), amount: Uint128::new(45) }).unwrap(),
+    funds: vec![] }));
+  assert_eq!(res.messages[3].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "hope_address".to_string(),
+    msg: to_json_binary(&Cw20ExecuteMsg::Transfer{ recipient: "seller1".to_string(), amount: Uint128::new(405) }).unwrap(),
+    funds: vec![] }));
+
+  let earnings = query_finder_earnings(deps.as_ref(), "finder1".to_string()).unwrap().earnings;
+  assert_eq!(earnings, vec![
+    Asset { denom: "hope".to_string(), amount: Uint128::new(45) },
+    Asset { denom: "ujuno".to_string(), amount: Uint128::new(90) }
+  ]);
+
+  //neither side of the trade can be its finder
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(5), None, "Hope.3".to_string());
+  let buy_msg = |finder: &str| ExecuteMsg::SetBidCoin {
+    nft_address: "collection1".to_string(),
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
+    sale_type: SaleType::FixedPrice,
+    token_id: Some("Hope.3".to_string()),
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(5) },
+    finder: Some(finder.to_string()),
+    origin_receiver: None
+  };
+  for finder in ["buyer1", "seller1"] {
+    match execute(deps.as_mut(), env.clone(), mock_info("buyer1", &coins(5, "ujuno")), buy_msg(finder)) {
+      Err(ContractError::InvalidFinder {}) => {}
+      _ => panic!("Must return invalid finder error"),
+    }
+  }
+
+  //a finder fee that rounds down to nothing is not paid out
+  let res = execute(deps.as_mut(), env.clone(), mock_info("buyer1", &coins(5, "ujuno")), buy_msg("finder1")).unwrap();
+  assert!(!res.messages.iter().any(|msg| msg.msg == CosmosMsg::Bank(BankMsg::Send { to_address: "finder1".to_string(), amount: coins(0, "ujuno") })));
+  assert_eq!(res.messages[2].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "seller1".to_string(), amount: coins(4, "ujuno") }));
+}
+
+#[test]
+fn reserved_listing(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  let expire = Timestamp::from_seconds(env.block.time.seconds() + 300);
+  for (token_id, denom, token_address) in [("Hope.1", "ujuno", None), ("Hope.2", "hope", Some("hope_address".to_string()))] {
+    let sell_msg = SellNft{
+      list_price: Asset { denom: denom.to_string(), amount: Uint128::new(1000) },
+      expire,
+      token_address,
+      instant_sell: None,
+      reserved_for: Some("buyer1".to_string()),
+      buy_now_price: None,
+      reserve_price: None,
+      min_bid_increment: None
+    };
+    let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{
+      sender: "seller1".to_string(),
+      token_id: token_id.to_string(),
+      msg: to_json_binary(&sell_msg).unwrap()
+    });
+    execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), msg).unwrap();
+  }
+
+  //nobody else can buy or bid, with coins or tokens
+  for sale_type in [SaleType::FixedPrice, SaleType::Auction] {
+    let msg = ExecuteMsg::SetBidCoin {
+      nft_address: "collection1".to_string(),
+      expire,
+      sale_type: sale_type.clone(),
+      token_id: Some("Hope.1".to_string()),
+      list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(1000) },
+      finder: None,
+      origin_receiver: None
+    };
+    match execute(deps.as_mut(), env.clone(), mock_info("buyer2", &coins(1000, "ujuno")), msg) {
+      Err(ContractError::ReservedAsk {}) => {}
+      _ => panic!("Must return reserved ask error"),
+    }
+
+    let bid_msg = BuyNft{
+      nft_address: "collection1".to_string(),
+      expire,
+      sale_type,
+      token_id: Some("Hope.2".to_string()),
+      finder: None,
+      origin_receiver: None
+    };
+    let msg = ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{ sender: "buyer2".to_string(), amount: Uint128::new(1000), msg: to_json_binary(&bid_msg).unwrap() }));
+    match execute(deps.as_mut(), env.clone(), mock_info("hope_address", &[]), msg) {
+      Err(ContractError::ReservedAsk {}) => {}
+      _ => panic!("Must return reserved ask error"),
+    }
+  }
+
+  //the reserved buyer settles with royalties paid
+  let res = bid_nft_with_coin_fixed_price(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "buyer1", "ujuno".to_string(), Uint128::new(1000)).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "admin1".to_string(), amount: coins(70, "ujuno") }));
+  assert_eq!(res.messages[2].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "seller1".to_string(), amount: coins(900, "ujuno") }));
+
+  bid_nft_with_token(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.2".to_string()), "hope_address", "buyer1".to_string(), Uint128::new(800)).unwrap();
+  let bids = query_bids(deps.as_ref(), "collection1".to_string(), "Hope.2".to_string(), None, None).unwrap();
+  assert_eq!(bids.bids.len(), 1);
+}
+
+#[test]
+fn buy_now_price(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  //buy-now price below the list price is rejected
+  let mut sell_msg = SellNft{
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(1000) },
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
+    token_address: None,
+    instant_sell: None,
+    reserved_for: None,
+    buy_now_price: Some(Uint128::new(999)),
+    reserve_price: None,
+    min_bid_increment: None
+  };
+  let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{ sender: "seller1".to_string(), token_id: "Hope.1".to_string(), msg: to_json_binary(&sell_msg).unwrap() });
+  match execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), msg) {
+    Err(ContractError::WrongConfig {}) => {}
+    _ => panic!("Must return wrong config error"),
+  }
+
+  sell_msg.buy_now_price = Some(Uint128::new(2000));
+  let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{ sender: "seller1".to_string(), token_id: "Hope.1".to_string(), msg: to_json_binary(&sell_msg).unwrap() });
+  execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), msg).unwrap();
+
+  //bids below the buy-now price, or in another denom, stay open
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bidder1", "ujuno".to_string(), Uint128::new(1999)).unwrap();
+  bid_nft_with_token(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "hope_address", "bidder2".to_string(), Uint128::new(3000)).unwrap();
+  assert_eq!(query_bids(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap().bids.len(), 2);
+
+  //a bid above the buy-now price settles at the buy-now price and refunds the excess and every other bid
+  let res = bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bidder3", "ujuno".to_string(), Uint128::new(2500)).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder3".to_string(), amount: coins(500, "ujuno") }));
+  assert_eq!(res.messages[1].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder1".to_string(), amount: coins(1999, "ujuno") }));
+  assert_eq!(res.messages[2].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "hope_address".to_string(),
+    msg: to_json_binary(&Cw20ExecuteMsg::Transfer{ recipient: "bidder2".to_string(), amount: Uint128::new(3000) }).unwrap(),
+    funds: vec![] }));
+  assert_eq!(res.messages[3].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "admin1".to_string(), amount: coins(140, "ujuno") }));
+  assert_eq!(res.messages[5].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "seller1".to_string(), amount: coins(1800, "ujuno") }));
+  let sale = res.events.iter().find(|event| event.ty == "sale").unwrap();
+  assert!(sale.attributes.iter().any(|attr| attr.key == "sale_type" && attr.value == "buy_now"));
+
+  assert!(query_ask(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string()).unwrap().ask.is_none());
+  assert_eq!(query_bids(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap().bids.len(), 0);
+  let history = query_sale_history_by_token_id(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap();
+  assert_eq!(history.sale_history[0].amount, Uint128::new(2000));
+}
+
+#[test]
+fn min_bid_rules(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  //only the owner sets the collection increment
+  let msg = ExecuteMsg::SetMinBidIncrement { nft_address: "collection1".to_string(), min_bid_increment: Decimal::percent(10) };
+  match execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg.clone()) {
+    Err(ContractError::Unauthorized {}) => {}
+    _ => panic!("Must return unauthorized error"),
+  }
+  execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+  let expire = Timestamp::from_seconds(env.block.time.seconds() + 300);
+  let listings = [
+    ("Hope.1", "ujuno", None, Some(Uint128::new(500)), None),
+    ("Hope.2", "hope", Some("hope_address".to_string()), None, Some(Decimal::zero()))
+  ];
+  for (token_id, denom, token_address, reserve_price, min_bid_increment) in listings {
+    let sell_msg = SellNft{
+      list_price: Asset { denom: denom.to_string(), amount: Uint128::new(1000) },
+      expire,
+      token_address,
+      instant_sell: None,
+      reserved_for: None,
+      buy_now_price: None,
+      reserve_price,
+      min_bid_increment
+    };
+    let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{ sender: "seller1".to_string(), token_id: token_id.to_string(), msg: to_json_binary(&sell_msg).unwrap() });
+    execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), msg).unwrap();
+  }
+
+  //bids below the reserve, or in another denom than the reserve, are rejected
+  let bid_msg = |amount: u128| ExecuteMsg::SetBidCoin {
+    nft_address: "collection1".to_string(),
+    expire,
+    sale_type: SaleType::Auction,
+    token_id: Some("Hope.1".to_string()),
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(amount) },
+    finder: None,
+    origin_receiver: None
+  };
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder1", &coins(499, "ujuno")), bid_msg(499)) {
+    Err(ContractError::BidBelowReserve {}) => {}
+    _ => panic!("Must return bid below reserve error"),
+  }
+  let token_bid = BuyNft{ nft_address: "collection1".to_string(), expire, sale_type: SaleType::Auction, token_id: Some("Hope.1".to_string()), finder: None, origin_receiver: None };
+  let msg = ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{ sender: "bidder1".to_string(), amount: Uint128::new(1000), msg: to_json_binary(&token_bid).unwrap() }));
+  match execute(deps.as_mut(), env.clone(), mock_info("hope_address", &[]), msg) {
+    Err(ContractError::BidBelowReserve {}) => {}
+    _ => panic!("Must return bid below reserve error"),
+  }
+
+  //later bids must beat the highest bid by 10%
+  execute(deps.as_mut(), env.clone(), mock_info("bidder1", &coins(500, "ujuno")), bid_msg(500)).unwrap();
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder2", &coins(549, "ujuno")), bid_msg(549)) {
+    Err(ContractError::BidIncrementTooLow {}) => {}
+    _ => panic!("Must return bid increment error"),
+  }
+  execute(deps.as_mut(), env.clone(), mock_info("bidder2", &coins(550, "ujuno")), bid_msg(550)).unwrap();
+
+  //a bidder raising their own bid only has to beat the others
+  execute(deps.as_mut(), env.clone(), mock_info("bidder2", &coins(550, "ujuno")), bid_msg(550)).unwrap();
+
+  //the listing override disables the increment
+  bid_nft_with_token(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.2".to_string()), "hope_address", "bidder1".to_string(), Uint128::new(100)).unwrap();
+  bid_nft_with_token(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.2".to_string()), "hope_address", "bidder2".to_string(), Uint128::new(50)).unwrap();
+  assert_eq!(query_bids(deps.as_ref(), "collection1".to_string(), "Hope.2".to_string(), None, None).unwrap().bids.len(), 2);
+
+  //the collection minimum covers listings without a reserve price
+  let msg = ExecuteMsg::SetMinBidPortion { nft_address: "collection1".to_string(), min_bid_portion: Decimal::percent(30) };
+  match execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg.clone()) {
+    Err(ContractError::Unauthorized {}) => {}
+    _ => panic!("Must return unauthorized error"),
+  }
+  execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+  let token_bid = BuyNft{ nft_address: "collection1".to_string(), expire, sale_type: SaleType::Auction, token_id: Some("Hope.2".to_string()), finder: None, origin_receiver: None };
+  let msg = ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{ sender: "bidder3".to_string(), amount: Uint128::new(299), msg: to_json_binary(&token_bid).unwrap() }));
+  match execute(deps.as_mut(), env.clone(), mock_info("hope_address", &[]), msg) {
+    Err(ContractError::BidBelowReserve {}) => {}
+    _ => panic!("Must return bid below reserve error"),
+  }
+  bid_nft_with_token(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.2".to_string()), "hope_address", "bidder3".to_string(), Uint128::new(300)).unwrap();
+}
+
+#[test]
+fn bid_eviction(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::SetBidLimit { bid_limit: 2 }).unwrap();
+
+  let sell_msg = SellNft{
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(1000) },
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 1000),
+    token_address: None,
+    instant_sell: None,
+    reserved_for: None,
+    buy_now_price: None,
+    reserve_price: None,
+    min_bid_increment: None
+  };
+  let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{ sender: "seller1".to_string(), token_id: "Hope.1".to_string(), msg: to_json_binary(&sell_msg).unwrap() });
+  execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), msg).unwrap();
+
+  let bid_msg = |amount: u128| ExecuteMsg::SetBidCoin {
+    nft_address: "collection1".to_string(),
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
+    sale_type: SaleType::Auction,
+    token_id: Some("Hope.1".to_string()),
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(amount) },
+    finder: None,
+    origin_receiver: None
+  };
+
+  //zero amount bids, collection bids and offers can not take up bid slots
+  match execute(deps.as_mut(), env.clone(), mock_info("spammer", &coins(0, "ujuno")), bid_msg(0)) {
+    Err(ContractError::NotEnoughFunds {}) => {}
+    _ => panic!("Must return not enough funds error"),
+  }
+  let collection_bid_msg = ExecuteMsg::SetBidCoin {
+    nft_address: "collection1".to_string(),
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
+    sale_type: SaleType::CollectionBid,
+    token_id: None,
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::zero() },
+    finder: None,
+    origin_receiver: None
+  };
+  match execute(deps.as_mut(), env.clone(), mock_info("spammer", &[]), collection_bid_msg) {
+    Err(ContractError::NotEnoughFunds {}) => {}
+    _ => panic!("Must return not enough funds error"),
+  }
+  for sale_type in [SaleType::Auction, SaleType::Offer] {
+    let token_bid = BuyNft{ nft_address: "collection1".to_string(), expire: Timestamp::from_seconds(env.block.time.seconds() + 300), sale_type, token_id: Some("Hope.1".to_string()), finder: None, origin_receiver: None };
+    let msg = ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{ sender: "spammer".to_string(), amount: Uint128::zero(), msg: to_json_binary(&token_bid).unwrap() }));
+    match execute(deps.as_mut(), env.clone(), mock_info("hope_address", &[]), msg) {
+      Err(ContractError::NotEnoughFunds {}) => {}
+      _ => panic!("Must return not enough funds error"),
+    }
+  }
+  assert!(query_bids(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap().bids.is_empty());
+
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bidder1", "ujuno".to_string(), Uint128::new(100)).unwrap();
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bidder2", "ujuno".to_string(), Uint128::new(200)).unwrap();
+
+  //a full token rejects bids that do not beat the lowest bid in their denom
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder3", &coins(100, "ujuno")), bid_msg(100)) {
+    Err(ContractError::BidCountExpired {}) => {}
+    _ => panic!("Must return bid count error"),
+  }
+  let token_bid = BuyNft{ nft_address: "collection1".to_string(), expire: Timestamp::from_seconds(env.block.time.seconds() + 300), sale_type: SaleType::Auction, token_id: Some("Hope.1".to_string()), finder: None, origin_receiver: None };
+  let msg = ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{ sender: "bidder3".to_string(), amount: Uint128::new(5000), msg: to_json_binary(&token_bid).unwrap() }));
+  match execute(deps.as_mut(), env.clone(), mock_info("hope_address", &[]), msg) {
+    Err(ContractError::BidCountExpired {}) => {}
+    _ => panic!("Must return bid count error"),
+  }
+
+  //a higher bid evicts and refunds the lowest one
+  let res = execute(deps.as_mut(), env.clone(), mock_info("bidder3", &coins(150, "ujuno")), bid_msg(150)).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder1".to_string(), amount: coins(100, "ujuno") }));
+  assert!(res.events.iter().any(|event| event.ty == "bid-removed"));
+  let bidders: Vec<String> = query_bids(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap().bids.into_iter().map(|bid| bid.bidder).collect();
+  assert_eq!(bidders, vec!["bidder2".to_string(), "bidder3".to_string()]);
+
+  //bidders already on the token can still replace their bid
+  let res = execute(deps.as_mut(), env.clone(), mock_info("bidder2", &coins(120, "ujuno")), bid_msg(120)).unwrap();
+  assert_eq!(res.messages.len(), 1);
+
+  //expired bids are evicted first, whatever their price
+  let mut later = env.clone();
+  later.block.time = later.block.time.plus_seconds(400);
+  let res = bid_nft_with_coin(deps.as_mut(), later.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bidder4", "ujuno".to_string(), Uint128::new(10)).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder2".to_string(), amount: coins(120, "ujuno") }));
+
+  //a bid failing the increment evicts nothing, not even the expired bid
+  let msg = ExecuteMsg::SetMinBidIncrement { nft_address: "collection1".to_string(), min_bid_increment: Decimal::percent(10) };
+  execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+  let late_bid = ExecuteMsg::SetBidCoin {
+    nft_address: "collection1".to_string(),
+    expire: later.block.time.plus_seconds(300),
+    sale_type: SaleType::Auction,
+    token_id: Some("Hope.1".to_string()),
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(10) },
+    finder: None,
+    origin_receiver: None
+  };
+  match execute(deps.as_mut(), later, mock_info("bidder5", &coins(10, "ujuno")), late_bid) {
+    Err(ContractError::BidIncrementTooLow {}) => {}
+    _ => panic!("Must return bid increment error"),
+  }
+  let bidders: Vec<String> = query_bids(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap().bids.into_iter().map(|bid| bid.bidder).collect();
+  assert_eq!(bidders, vec!["bidder3".to_string(), "bidder4".to_string()]);
+}
+
+#[test]
+fn cleanup_expired(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+
+  //only the owner configures the bounty, anyone funds it
+  let msg = ExecuteMsg::SetCleanupBounty { bounty: Some(Asset { denom: "ujuno".to_string(), amount: Uint128::new(5) }) };
+  match execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg.clone()) {
+    Err(ContractError::Unauthorized {}) => {}
+    _ => panic!("Must return unauthorized error"),
+  }
+  execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+  execute(deps.as_mut(), env.clone(), mock_info("funder", &coins(4, "ujuno")), ExecuteMsg::FundCleanupBounty {}).unwrap();
+
+  //the denom is locked while the pool holds funds
+  match execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::SetCleanupBounty { bounty: None }) {
+    Err(ContractError::CleanupPoolNotEmpty {}) => {}
+    _ => panic!("Must return pool not empty error"),
+  }
+
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.1".to_string());
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bidder1", "ujuno".to_string(), Uint128::new(100)).unwrap();
+  collection_bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), None, "bidder2", "ujuno".to_string(), Uint128::new(200)).unwrap();
+
+  let mut later = env.clone();
+  later.block.time = later.block.time.plus_seconds(200);
+  sell_nft(deps.as_mut(), later.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.2".to_string());
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.2".to_string()), "bidder3", "ujuno".to_string(), Uint128::new(300)).unwrap();
+
+  //nothing has expired yet
+  let res = execute(deps.as_mut(), env.clone(), mock_info("cranker", &[]), ExecuteMsg::CleanupExpired { limit: None }).unwrap();
+  assert_eq!(res.messages.len(), 0);
+
+  //every removal counts against the limit, so the expired ask's bid goes first and the ask stays.
+  //Orders still inside the grace period are cleaned without earning the bounty
+  let mut expired = env.clone();
+  expired.block.time = expired.block.time.plus_seconds(400);
+  let res = execute(deps.as_mut(), expired.clone(), mock_info("cranker", &[]), ExecuteMsg::CleanupExpired { limit: Some(1) }).unwrap();
+  assert_eq!(res.messages.len(), 1);
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder1".to_string(), amount: coins(100, "ujuno") }));
+  assert!(query_ask(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string()).unwrap().ask.is_some());
+
+  //the ask goes back to the seller, it escrows no funds and earns nothing even past the grace period
+  let mut past_grace = env.clone();
+  past_grace.block.time = past_grace.block.time.plus_seconds(300 + CLEANUP_GRACE_SECONDS);
+  let res = execute(deps.as_mut(), past_grace.clone(), mock_info("cranker", &[]), ExecuteMsg::CleanupExpired { limit: Some(1) }).unwrap();
+  assert_eq!(res.messages.len(), 1);
+  assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "collection1".to_string(),
+    msg: to_json_binary(&Cw721ExecuteMsg::TransferNft { recipient: "seller1".to_string(), token_id: "Hope.1".to_string() }).unwrap(),
+    funds: vec![] }));
+  assert!(query_ask(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string()).unwrap().ask.is_none());
+
+  //empty escrow left by older versions is dropped without a refund
+  let zero_bid = Bid {
+    collection: "collection1".to_string(),
+    token_id: "Hope.3".to_string(),
+    bidder: "bidder4".to_string(),
+    token_address: None,
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::zero() },
+    expires_at: Timestamp::from_seconds(env.block.time.seconds() + 300),
+    seller: "seller1".to_string(),
+    finder: None
+  };
+  bids().save(deps.as_mut().storage, ("collection1".to_string(), "Hope.3".to_string(), "bidder4".to_string()), &zero_bid).unwrap();
+
+  //bidder3's bid earns 2% of its escrow capped at the bounty, bidder2's own collection bid earns nothing,
+  //and the payout is capped by the pool
+  let res = execute(deps.as_mut(), past_grace.clone(), mock_info("bidder2", &[]), ExecuteMsg::CleanupExpired { limit: None }).unwrap();
+  assert_eq!(res.messages.len(), 4);
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder3".to_string(), amount: coins(300, "ujuno") }));
+  assert_eq!(res.messages[1].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "collection1".to_string(),
+    msg: to_json_binary(&Cw721ExecuteMsg::TransferNft { recipient: "seller1".to_string(), token_id: "Hope.2".to_string() }).unwrap(),
+    funds: vec![] }));
+  assert_eq!(res.messages[2].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder2".to_string(), amount: coins(200, "ujuno") }));
+  assert_eq!(res.messages[3].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder2".to_string(), amount: coins(4, "ujuno") }));
+  assert!(res.events.iter().any(|event| event.ty == "expired-orders-cleaned" && event.attributes.iter().any(|attr| attr.key == "removed" && attr.value == "4")));
+  assert!(bids().may_load(deps.as_ref().storage, ("collection1".to_string(), "Hope.3".to_string(), "bidder4".to_string())).unwrap().is_none());
+  assert_eq!(query_cleanup_bounty(deps.as_ref()).unwrap().pool, Uint128::zero());
+}
+
+#[test]
+fn orders_by_expiry(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+  add_contract(deps.as_mut(), env.clone() , "collection2".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+
+  let at = |seconds: u64| {
+    let mut env = env.clone();
+    env.block.time = env.block.time.plus_seconds(seconds);
+    env
+  };
+
+  //asks from different collections expire at +300, +400 and +500
+  sell_nft(deps.as_mut(), at(0), "collection2", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.1".to_string());
+  sell_nft(deps.as_mut(), at(100), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.2".to_string());
+  sell_nft(deps.as_mut(), at(200), "collection1", "seller2".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.3".to_string());
+
+  let expires_before = at(450).block.time;
+  let asks = query_asks_by_expiry(deps.as_ref(), expires_before, None, Some(1)).unwrap().asks;
+  assert_eq!(asks[0].token_id, "Hope.1".to_string());
+  let start_after = AskExpiryOffset { expires_at: asks[0].expires_at, collection: asks[0].collection.clone(), token_id: asks[0].token_id.clone() };
+  let asks = query_asks_by_expiry(deps.as_ref(), expires_before, Some(start_after), None).unwrap().asks;
+  assert_eq!(asks.len(), 1);
+  assert_eq!(asks[0].token_id, "Hope.2".to_string());
+
+  bid_nft_with_coin(deps.as_mut(), at(100), "collection1".to_string(), Some("Hope.3".to_string()), "bidder1", "ujuno".to_string(), Uint128::new(100)).unwrap();
+  bid_nft_with_coin(deps.as_mut(), at(0), "collection1".to_string(), Some("Hope.3".to_string()), "bidder2", "ujuno".to_string(), Uint128::new(100)).unwrap();
+  let bids = query_bids_by_expiry(deps.as_ref(), at(350).block.time, None, None).unwrap().bids;
+  assert_eq!(bids.len(), 1);
+  assert_eq!(bids[0].bidder, "bidder2".to_string());
+
+  collection_bid_nft_with_coin(deps.as_mut(), at(0), "collection1".to_string(), None, "bidder3", "ujuno".to_string(), Uint128::new(100)).unwrap();
+  collection_bid_nft_with_coin(deps.as_mut(), at(100), "collection2".to_string(), None, "bidder4", "ujuno".to_string(), Uint128::new(100)).unwrap();
+  let bids = query_collection_bids_by_expiry(deps.as_ref(), at(1000).block.time, None, None).unwrap().bids;
+  assert_eq!(bids.len(), 2);
+  let start_after = CollectionBidExpiryOffset { expires_at: bids[0].expires_at, collection: bids[0].collection.clone(), bidder: bids[0].bidder.clone() };
+  let bids = query_collection_bids_by_expiry(deps.as_ref(), at(1000).block.time, Some(start_after), None).unwrap().bids;
+  assert_eq!(bids.len(), 1);
+  assert_eq!(bids[0].bidder, "bidder4".to_string());
+
+  //asks stored before the expiry index existed only show up once the owner backfills them
+  let mut legacy_ask = query_ask(deps.as_ref(), "collection2".to_string(), "Hope.1".to_string()).unwrap().ask.unwrap();
+  legacy_ask.token_id = "Hope.0".to_string();
+  Map::<(String, String), Ask>::new("asks").save(deps.as_mut().storage, ("collection2".to_string(), "Hope.0".to_string()), &legacy_ask).unwrap();
+  assert_eq!(query_asks_by_expiry(deps.as_ref(), expires_before, None, None).unwrap().asks.len(), 2);
+
+  match execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), ExecuteMsg::BackfillExpiryIndexes { limit: None }) {
+    Err(ContractError::Unauthorized {}) => {}
+    _ => panic!("Must return unauthorized error"),
+  }
+  let backfill = |deps: DepsMut, limit: u32| {
+    let res = execute(deps, env.clone(), mock_info("owner", &[]), ExecuteMsg::BackfillExpiryIndexes { limit: Some(limit) }).unwrap();
+    res.attributes.iter().find(|attr| attr.key == "done").unwrap().value.clone()
+  };
+  //4 asks, 2 bids and 2 collection bids
+  assert_eq!(backfill(deps.as_mut(), 3), "false");
+  assert_eq!(backfill(deps.as_mut(), 4), "false");
+  assert_eq!(backfill(deps.as_mut(), 2), "true");
+  assert_eq!(backfill(deps.as_mut(), 2), "true");
+  let asks = query_asks_by_expiry(deps.as_ref(), expires_before, None, None).unwrap().asks;
+  assert_eq!(asks.len(), 3);
+  assert_eq!(asks[0].token_id, "Hope.0".to_string());
+  assert_eq!(query_collection_bids_by_expiry(deps.as_ref(), at(1000).block.time, None, None).unwrap().bids.len(), 2);
+}
+
+/// Lists Hope.1 for ujuno and Hope.2 for hope tokens with a bid and a collection bid, then returns the env
+/// at a time where the asks and the bids are expired or not
+fn expiry_fixture(mut deps: DepsMut, env: Env, ask_expired: bool, bid_expired: bool) -> Env {
+  setup_contract(deps.branch());
+  add_contract(deps.branch(), env.clone(), "collection1".to_string());
+  add_coin(deps.branch(), env.clone(), "ujuno".to_string());
+  add_token(deps.branch(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  let expire_at = |expired: bool| Timestamp::from_seconds(env.block.time.seconds() + if expired { 100 } else { 1000 });
+  for (token_id, denom, token_address) in [("Hope.1", "ujuno", None), ("Hope.2", "hope", Some("hope_address".to_string()))] {
+    let sell_msg = SellNft{
+      list_price: Asset { denom: denom.to_string(), amount: Uint128::new(1000) },
+      expire: expire_at(ask_expired),
+      token_address,
+      instant_sell: None,
+      reserved_for: None,
+      buy_now_price: None,
+      reserve_price: None,
+      min_bid_increment: None
+    };
+    let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{ sender: "seller1".to_string(), token_id: token_id.to_string(), msg: to_json_binary(&sell_msg).unwrap() });
+    execute(deps.branch(), env.clone(), mock_info("collection1", &[]), msg).unwrap();
+  }
+
+  for (sale_type, token_id, bidder) in [(SaleType::Auction, Some("Hope.1".to_string()), "bidder1"), (SaleType::CollectionBid, None, "bidder2")] {
+    let msg = ExecuteMsg::SetBidCoin {
+      nft_address: "collection1".to_string(),
+      expire: expire_at(bid_expired),
+      sale_type,
+      token_id,
+      list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(500) },
+      finder: None,
+      origin_receiver: None
+    };
+    execute(deps.branch(), env.clone(), mock_info(bidder, &coins(500, "ujuno")), msg).unwrap();
+  }
+
+  let mut now = env;
+  now.block.time = now.block.time.plus_seconds(200);
+  now
+}
+
+#[test]
+fn expiry_model(){
+  let env = mock_env();
+
+  let accept_bid = ExecuteMsg::AcceptBid { nft_address: "collection1".to_string(), token_id: "Hope.1".to_string(), bidder: "bidder1".to_string() };
+  let accept_collection_bid = ExecuteMsg::AcceptCollectionBid { nft_address: "collection1".to_string(), token_id: "Hope.1".to_string(), bidder: "bidder2".to_string() };
+  let coin_bid = |sale_type: SaleType| ExecuteMsg::SetBidCoin {
+    nft_address: "collection1".to_string(),
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 1000),
+    sale_type,
+    token_id: Some("Hope.1".to_string()),
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(1000) },
+    finder: None,
+    origin_receiver: None
+  };
+  let token_bid = |sale_type: SaleType| ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{
+    sender: "buyer1".to_string(),
+    amount: Uint128::new(1000),
+    msg: to_json_binary(&BuyNft{ nft_address: "collection1".to_string(), expire: Timestamp::from_seconds(env.block.time.seconds() + 1000), sale_type, token_id: Some("Hope.2".to_string()), finder: None, origin_receiver: None }).unwrap()
+  }));
+  let update_price = ExecuteMsg::UpdateAskPrice { nft_address: "collection1".to_string(), token_id: "Hope.1".to_string(), list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(2000) }, token_address: None };
+  let withdraw = ExecuteMsg::WithdrawNft { nft_address: "collection1".to_string(), token_id: "Hope.1".to_string() };
+  let remove_bid = ExecuteMsg::RemoveBid { nft_address: "collection1".to_string(), token_id: "Hope.1".to_string() };
+  let remove_collection_bid = ExecuteMsg::RemoveCollectionBid { nft_address: "collection1".to_string() };
+
+  for ask_expired in [false, true] {
+    for bid_expired in [false, true] {
+      //accepting checks the bid first, then the ask
+      let accept_result = if bid_expired { Some("BidExpired") } else if ask_expired { Some("AskExpired") } else { None };
+      let ask_result = if ask_expired { Some("AskExpired") } else { None };
+      let cases: Vec<(&str, MessageInfo, ExecuteMsg, Option<&str>)> = vec![
+        ("accept bid", mock_info("seller1", &[]), accept_bid.clone(), accept_result),
+        ("accept collection bid", mock_info("seller1", &[]), accept_collection_bid.clone(), accept_result),
+        ("buy with coin", mock_info("buyer1", &coins(1000, "ujuno")), coin_bid(SaleType::FixedPrice), ask_result),
+        ("buy with token", mock_info("hope_address", &[]), token_bid(SaleType::FixedPrice), ask_result),
+        ("bid with coin", mock_info("buyer1", &coins(1000, "ujuno")), coin_bid(SaleType::Auction), ask_result),
+        ("bid with token", mock_info("hope_address", &[]), token_bid(SaleType::Auction), ask_result),
+        ("update price", mock_info("seller1", &[]), update_price.clone(), ask_result),
+        ("withdraw", mock_info("seller1", &[]), withdraw.clone(), None),
+        ("remove bid", mock_info("bidder1", &[]), remove_bid.clone(), None),
+        ("remove collection bid", mock_info("bidder2", &[]), remove_collection_bid.clone(), None),
+      ];
+
+      for (name, info, msg, expected) in cases {
+        let mut deps = mock_dependencies();
+        let now = expiry_fixture(deps.as_mut(), env.clone(), ask_expired, bid_expired);
+        let res = execute(deps.as_mut(), now, info, msg);
+        match (expected, res) {
+          (None, Ok(_)) => {}
+          (Some(error), Err(err)) if format!("{:?}", err).starts_with(error) => {}
+          (expected, res) => panic!("{} with ask expired {} and bid expired {}: expected {:?}, got {:?}", name, ask_expired, bid_expired, expected, res.err()),
+        }
+      }
+    }
+  }
+}
+
+#[test]
+fn update_ask(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.1".to_string());
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bidder1", "ujuno".to_string(), Uint128::new(100)).unwrap();
+  bid_nft_with_token(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "hope_address", "bidder2".to_string(), Uint128::new(200)).unwrap();
+
+  let update = |list_price: Option<Asset>, token_address: Option<&str>, expire: Option<u64>, buy_now_price: Option<u128>| ExecuteMsg::UpdateAsk(UpdateAskMsg {
+    nft_address: "collection1".to_string(),
+    token_id: "Hope.1".to_string(),
+    list_price,
+    token_address: token_address.map(|address| address.to_string()),
+    expire: expire.map(|seconds| Timestamp::from_seconds(env.block.time.seconds() + seconds)),
+    buy_now_price: buy_now_price.map(Uint128::new),
+    reserve_price: None,
+    min_bid_increment: None,
+    clear: vec![]
+  });
+  let hope_price = Asset { denom: "hope".to_string(), amount: Uint128::new(500) };
+
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder1", &[]), update(None, None, Some(1000), None)) {
+    Err(ContractError::Unauthorized {}) => {}
+    _ => panic!("Must return unauthorized error"),
+  }
+  match execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), update(None, Some("hope_address"), None, None)) {
+    Err(ContractError::WrongConfig {}) => {}
+    _ => panic!("Must return wrong config error"),
+  }
+  match execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), update(Some(hope_price.clone()), None, None, None)) {
+    Err(ContractError::WrongCoinDenom {}) => {}
+    _ => panic!("Must return wrong coin denom error"),
+  }
+  match execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), update(None, None, Some(0), None)) {
+    Err(ContractError::AskExpired {}) => {}
+    _ => panic!("Must return ask expired error"),
+  }
+  match execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), update(Some(hope_price.clone()), Some("hope_address"), None, Some(499))) {
+    Err(ContractError::WrongConfig {}) => {}
+    _ => panic!("Must return wrong config error"),
+  }
+
+  //switching to hope tokens refunds the coin bid and keeps the token bid
+  let res = execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), update(Some(hope_price.clone()), Some("hope_address"), Some(1000), None)).unwrap();
+  assert_eq!(res.messages.len(), 1);
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder1".to_string(), amount: coins(100, "ujuno") }));
+  assert!(res.events.iter().any(|event| event.ty == "ask-updated"));
+  let ask = query_ask(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string()).unwrap().ask.unwrap();
+  assert_eq!(ask.list_price, hope_price);
+  assert_eq!(ask.expires_at, Timestamp::from_seconds(env.block.time.seconds() + 1000));
+  let bidders: Vec<String> = query_bids(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap().bids.into_iter().map(|bid| bid.bidder).collect();
+  assert_eq!(bidders, vec!["bidder2".to_string()]);
+
+  //the extended ask takes bids after its original expiry
+  let mut later = env.clone();
+  later.block.time = later.block.time.plus_seconds(500);
+  bid_nft_with_token(deps.as_mut(), later, "collection1".to_string(), Some("Hope.1".to_string()), "hope_address", "bidder3".to_string(), Uint128::new(300)).unwrap();
+
+  //optional settings are cleared explicitly, but not while setting them
+  execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), update(None, None, None, Some(800))).unwrap();
+  let clear = |buy_now_price: Option<u128>| ExecuteMsg::UpdateAsk(UpdateAskMsg {
+    nft_address: "collection1".to_string(),
+    token_id: "Hope.1".to_string(),
+    list_price: None,
+    token_address: None,
+    expire: None,
+    buy_now_price: buy_now_price.map(Uint128::new),
+    reserve_price: None,
+    min_bid_increment: None,
+    clear: vec![AskSetting::BuyNowPrice, AskSetting::ReservePrice]
+  });
+  match execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), clear(Some(900))) {
+    Err(ContractError::WrongConfig {}) => {}
+    _ => panic!("Must return wrong config error"),
+  }
+  execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), clear(None)).unwrap();
+  let ask = query_ask(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string()).unwrap().ask.unwrap();
+  assert_eq!((ask.buy_now_price, ask.reserve_price), (None, None));
+
+  //a new denom drops the buy now price set in the old one
+  execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), update(None, None, None, Some(800))).unwrap();
+  execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), update(Some(Asset { denom: "ujuno".to_string(), amount: Uint128::new(500) }), None, None, None)).unwrap();
+  let ask = query_ask(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string()).unwrap().ask.unwrap();
+  assert_eq!(ask.buy_now_price, None);
+
+  //the price only update refunds bids in the old denom as well
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bidder4", "ujuno".to_string(), Uint128::new(100)).unwrap();
+  let msg = ExecuteMsg::UpdateAskPrice { nft_address: "collection1".to_string(), token_id: "Hope.1".to_string(), list_price: Asset { denom: "hope".to_string(), amount: Uint128::new(500) }, token_address: Some("hope_address".to_string()) };
+  let res = execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), msg).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder4".to_string(), amount: coins(100, "ujuno") }));
+  assert!(query_bids(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap().bids.is_empty());
+}
+
+#[test]
+fn update_bid(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  let sell_msg = SellNft{
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(1000) },
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 1000),
+    token_address: None,
+    instant_sell: None,
+    reserved_for: None,
+    buy_now_price: Some(Uint128::new(2000)),
+    reserve_price: None,
+    min_bid_increment: None
+  };
+  let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{ sender: "seller1".to_string(), token_id: "Hope.1".to_string(), msg: to_json_binary(&sell_msg).unwrap() });
+  execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), msg).unwrap();
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "hope".to_string(), Uint128::new(1000), Some("hope_address".to_string()), "Hope.2".to_string());
+
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bidder1", "ujuno".to_string(), Uint128::new(500)).unwrap();
+  bid_nft_with_token(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.2".to_string()), "hope_address", "bidder2".to_string(), Uint128::new(300)).unwrap();
+  collection_bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), None, "bidder3", "ujuno".to_string(), Uint128::new(100)).unwrap();
+
+  let update = |token_id: Option<&str>, expire: Option<u64>| ExecuteMsg::UpdateBid {
+    nft_address: "collection1".to_string(),
+    token_id: token_id.map(|token_id| token_id.to_string()),
+    expire: expire.map(|seconds| Timestamp::from_seconds(env.block.time.seconds() + seconds))
+  };
+  let token_update = |sender: &str, amount: u128, token_id: Option<&str>| ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{
+    sender: sender.to_string(),
+    amount: Uint128::new(amount),
+    msg: to_json_binary(&TokenHookMsg::UpdateBid { nft_address: "collection1".to_string(), token_id: token_id.map(|token_id| token_id.to_string()), expire: None }).unwrap()
+  }));
+
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder1", &[]), update(Some("Hope.1"), None)) {
+    Err(ContractError::WrongConfig {}) => {}
+    _ => panic!("Must return wrong config error"),
+  }
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder1", &coins(300, "uatom")), update(Some("Hope.1"), None)) {
+    Err(ContractError::WrongCoinDenom {}) => {}
+    _ => panic!("Must return wrong coin denom error"),
+  }
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder1", &[]), update(Some("Hope.1"), Some(0))) {
+    Err(ContractError::BidExpired {}) => {}
+    _ => panic!("Must return bid expired error"),
+  }
+  match execute(deps.as_mut(), env.clone(), mock_info("hope_address", &[]), token_update("bidder3", 50, None)) {
+    Err(ContractError::WrongTokenContractError {}) => {}
+    _ => panic!("Must return wrong token contract error"),
+  }
+
+  //only the top-up is sent and nothing is refunded
+  let res = execute(deps.as_mut(), env.clone(), mock_info("bidder1", &coins(300, "ujuno")), update(Some("Hope.1"), Some(600))).unwrap();
+  assert_eq!(res.messages.len(), 0);
+  assert!(res.events.iter().any(|event| event.ty == "bid-updated"));
+  let bid = bids().load(deps.as_ref().storage, bid_key(&"collection1".to_string(), &"Hope.1".to_string(), &"bidder1".to_string())).unwrap();
+  assert_eq!(bid.list_price.amount, Uint128::new(800));
+  assert_eq!(bid.expires_at, Timestamp::from_seconds(env.block.time.seconds() + 600));
+
+  execute(deps.as_mut(), env.clone(), mock_info("hope_address", &[]), token_update("bidder2", 200, Some("Hope.2"))).unwrap();
+  let bid = bids().load(deps.as_ref().storage, bid_key(&"collection1".to_string(), &"Hope.2".to_string(), &"bidder2".to_string())).unwrap();
+  assert_eq!(bid.list_price.amount, Uint128::new(500));
+
+  execute(deps.as_mut(), env.clone(), mock_info("bidder3", &coins(50, "ujuno")), update(None, None)).unwrap();
+  let collection_bid = collection_bids().load(deps.as_ref().storage, collection_bid_key(&"collection1".to_string(), &"bidder3".to_string())).unwrap();
+  assert_eq!(collection_bid.list_price.amount, Uint128::new(150));
+
+  let activities = query_activities_by_user(deps.as_ref(), "bidder1".to_string(), None, None).unwrap().activities;
+  assert_eq!(activities[0].activity_type, ActivityType::BidUpdate);
+
+  //a top-up reaching the buy-now price buys the NFT and refunds the excess
+  let res = execute(deps.as_mut(), env.clone(), mock_info("bidder1", &coins(1500, "ujuno")), update(Some("Hope.1"), None)).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder1".to_string(), amount: coins(300, "ujuno") }));
+  assert_eq!(res.messages[3].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "seller1".to_string(), amount: coins(1800, "ujuno") }));
+  assert!(query_ask(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string()).unwrap().ask.is_none());
+  assert_eq!(query_bids(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap().bids.len(), 0);
+
+  //an outbid bidder can still change the expiry, but a raise must beat the increment
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.3".to_string());
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.3".to_string()), "bidder4", "ujuno".to_string(), Uint128::new(400)).unwrap();
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.3".to_string()), "bidder5", "ujuno".to_string(), Uint128::new(500)).unwrap();
+  let msg = ExecuteMsg::SetMinBidIncrement { nft_address: "collection1".to_string(), min_bid_increment: Decimal::percent(10) };
+  execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+  execute(deps.as_mut(), env.clone(), mock_info("bidder4", &[]), update(Some("Hope.3"), Some(200))).unwrap();
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder4", &coins(10, "ujuno")), update(Some("Hope.3"), None)) {
+    Err(ContractError::BidIncrementTooLow {}) => {}
+    _ => panic!("Must return bid increment error"),
+  }
+}
+
+#[test]
+fn counter_offers(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.1".to_string());
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "hope".to_string(), Uint128::new(1000), Some("hope_address".to_string()), "Hope.2".to_string());
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bidder1", "ujuno".to_string(), Uint128::new(600)).unwrap();
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.1".to_string()), "bidder2", "ujuno".to_string(), Uint128::new(500)).unwrap();
+  bid_nft_with_token(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.2".to_string()), "hope_address", "bidder3".to_string(), Uint128::new(400)).unwrap();
+
+  let counter = |token_id: &str, bidder: &str, price: u128, expire: u64| ExecuteMsg::MakeCounterOffer(MakeCounterOfferMsg {
+    nft_address: "collection1".to_string(),
+    token_id: token_id.to_string(),
+    bidder: bidder.to_string(),
+    price: Uint128::new(price),
+    expire: Timestamp::from_seconds(env.block.time.seconds() + expire)
+  });
+  let accept = ExecuteMsg::AcceptCounterOffer { nft_address: "collection1".to_string(), token_id: "Hope.1".to_string() };
+
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder2", &[]), counter("Hope.1", "bidder1", 800, 100)) {
+    Err(ContractError::Unauthorized {}) => {}
+    _ => panic!("Must return unauthorized error"),
+  }
+  match execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), counter("Hope.1", "bidder1", 600, 100)) {
+    Err(ContractError::WrongConfig {}) => {}
+    _ => panic!("Must return wrong config error"),
+  }
+  match execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), counter("Hope.1", "bidder1", 800, 0)) {
+    Err(ContractError::CounterOfferExpired {}) => {}
+    _ => panic!("Must return counter offer expired error"),
+  }
+
+  execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), counter("Hope.1", "bidder1", 800, 100)).unwrap();
+  execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), counter("Hope.2", "bidder3", 900, 100)).unwrap();
+  assert_eq!(query_counter_offers_by_bidder(deps.as_ref(), "bidder1".to_string(), None, None).unwrap().counter_offers.len(), 1);
+  assert_eq!(query_counter_offers_by_seller(deps.as_ref(), "seller1".to_string(), None, None).unwrap().counter_offers.len(), 2);
+
+  //only the seller withdraws a counter-offer
+  let remove = ExecuteMsg::RemoveCounterOffer { nft_address: "collection1".to_string(), token_id: "Hope.2".to_string(), bidder: "bidder3".to_string() };
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder3", &[]), remove.clone()) {
+    Err(ContractError::Unauthorized {}) => {}
+    _ => panic!("Must return unauthorized error"),
+  }
+  execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), remove).unwrap();
+  assert!(query_counter_offer(deps.as_ref(), "collection1".to_string(), "Hope.2".to_string(), "bidder3".to_string()).unwrap().counter_offer.is_none());
+
+  //the bidder tops up exactly the difference before the counter-offer expires
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder1", &coins(100, "ujuno")), accept.clone()) {
+    Err(ContractError::NotEnoughFunds {}) => {}
+    _ => panic!("Must return not enough funds error"),
+  }
+  let mut later = env.clone();
+  later.block.time = later.block.time.plus_seconds(150);
+  match execute(deps.as_mut(), later, mock_info("bidder1", &coins(200, "ujuno")), accept.clone()) {
+    Err(ContractError::CounterOfferExpired {}) => {}
+    _ => panic!("Must return counter offer expired error"),
+  }
+
+  let res = execute(deps.as_mut(), env.clone(), mock_info("bidder1", &coins(200, "ujuno")), accept).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder2".to_string(), amount: coins(500, "ujuno") }));
+  assert_eq!(res.messages[1].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "admin1".to_string(), amount: coins(56, "ujuno") }));
+  assert_eq!(res.messages[3].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "seller1".to_string(), amount: coins(720, "ujuno") }));
+  let sale = res.events.iter().find(|event| event.ty == "sale").unwrap();
+  assert!(sale.attributes.iter().any(|attr| attr.key == "sale_type" && attr.value == "counter_offer"));
+  assert!(query_ask(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string()).unwrap().ask.is_none());
+  assert_eq!(query_bids(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap().bids.len(), 0);
+  assert!(query_counter_offer(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), "bidder1".to_string()).unwrap().counter_offer.is_none());
+
+  //cw20 bidders accept by sending the difference in tokens
+  execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), counter("Hope.2", "bidder3", 700, 100)).unwrap();
+  let msg = ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{
+    sender: "bidder3".to_string(),
+    amount: Uint128::new(300),
+    msg: to_json_binary(&TokenHookMsg::AcceptCounterOffer { nft_address: "collection1".to_string(), token_id: "Hope.2".to_string() }).unwrap()
+  }));
+  let res = execute(deps.as_mut(), env.clone(), mock_info("hope_address", &[]), msg).unwrap();
+  assert_eq!(res.messages[2].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "hope_address".to_string(),
+    msg: to_json_binary(&Cw20ExecuteMsg::Transfer{ recipient: "seller1".to_string(), amount: Uint128::new(630) }).unwrap(),
+    funds: vec![] }));
+
+  //counter-offers go with the bid they answer and with the listing
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.3".to_string());
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.3".to_string()), "bidder4", "ujuno".to_string(), Uint128::new(600)).unwrap();
+  bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), Some("Hope.3".to_string()), "bidder5", "ujuno".to_string(), Uint128::new(500)).unwrap();
+  execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), counter("Hope.3", "bidder4", 800, 100)).unwrap();
+  execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), counter("Hope.3", "bidder5", 800, 100)).unwrap();
+  let remove_bid = ExecuteMsg::RemoveBid { nft_address: "collection1".to_string(), token_id: "Hope.3".to_string() };
+  execute(deps.as_mut(), env.clone(), mock_info("bidder4", &[]), remove_bid).unwrap();
+  assert!(query_counter_offer(deps.as_ref(), "collection1".to_string(), "Hope.3".to_string(), "bidder4".to_string()).unwrap().counter_offer.is_none());
+  assert!(query_counter_offer(deps.as_ref(), "collection1".to_string(), "Hope.3".to_string(), "bidder5".to_string()).unwrap().counter_offer.is_some());
+  let withdraw = ExecuteMsg::WithdrawNft { nft_address: "collection1".to_string(), token_id: "Hope.3".to_string() };
+  execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), withdraw).unwrap();
+  assert!(query_counter_offer(deps.as_ref(), "collection1".to_string(), "Hope.3".to_string(), "bidder5".to_string()).unwrap().counter_offer.is_none());
+}
+
+#[test]
+fn unlisted_offers(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::AddHook { hook_type: HookType::Bid, address: "bid_hook".to_string() }).unwrap();
+
+  let offer = |token_id: Option<String>, expire: u64| ExecuteMsg::SetBidCoin {
+    nft_address: "collection1".to_string(),
+    expire: Timestamp::from_seconds(env.block.time.seconds() + expire),
+    sale_type: SaleType::Offer,
+    token_id,
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(1000) },
+    finder: None,
+    origin_receiver: None
+  };
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder1", &coins(1000, "ujuno")), offer(None, 100)) {
+    Err(ContractError::WrongConfig {}) => {}
+    _ => panic!("Must return wrong config error"),
+  }
+  match execute(deps.as_mut(), env.clone(), mock_info("bidder1", &coins(1000, "ujuno")), offer(Some("Hope.1".to_string()), 0)) {
+    Err(ContractError::BidExpired {}) => {}
+    _ => panic!("Must return bid expired error"),
+  }
+
+  //offers need no ask, a new offer from the same bidder replaces and refunds the old one
+  execute(deps.as_mut(), env.clone(), mock_info("bidder1", &coins(1000, "ujuno")), offer(Some("Hope.1".to_string()), 100)).unwrap();
+  let res = execute(deps.as_mut(), env.clone(), mock_info("bidder1", &coins(1000, "ujuno")), offer(Some("Hope.1".to_string()), 200)).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "bidder1".to_string(), amount: coins(1000, "ujuno") }));
+  let placed = query_offer(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), "bidder1".to_string()).unwrap().offer.unwrap();
+  assert_eq!(res.messages[1].msg, MarketplaceHookMsg::OfferHook(OfferHookMsg { offer: placed }).into_cosmos_msg("bid_hook".to_string()).unwrap());
+
+  let msg = ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{
+    sender: "bidder2".to_string(),
+    amount: Uint128::new(500),
+    msg: to_json_binary(&BuyNft{
+      nft_address: "collection1".to_string(),
+      expire: Timestamp::from_seconds(env.block.time.seconds() + 100),
+      sale_type: SaleType::Offer,
+      token_id: Some("Hope.1".to_string()),
+      finder: None,
+      origin_receiver: None
+    }).unwrap()
+  }));
+  execute(deps.as_mut(), env.clone(), mock_info("hope_address", &[]), msg).unwrap();
+  assert_eq!(query_offers_by_token(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap().offers.len(), 2);
+  assert_eq!(query_offers_by_bidder(deps.as_ref(), "bidder2".to_string(), None, None).unwrap().offers[0].token_address, Some("hope_address".to_string()));
+
+  //the bidder withdraws their offer
+  let remove = ExecuteMsg::RemoveOffer { nft_address: "collection1".to_string(), token_id: "Hope.1".to_string() };
+  let res = execute(deps.as_mut(), env.clone(), mock_info("bidder2", &[]), remove).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "hope_address".to_string(),
+    msg: to_json_binary(&Cw20ExecuteMsg::Transfer{ recipient: "bidder2".to_string(), amount: Uint128::new(500) }).unwrap(),
+    funds: vec![] }));
+  assert!(query_offer(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), "bidder2".to_string()).unwrap().offer.is_none());
+
+  //the owner accepts by sending the NFT with an accept-offer payload
+  let accept = |bidder: &str| ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{
+    sender: "owner1".to_string(),
+    token_id: "Hope.1".to_string(),
+    msg: to_json_binary(&NftHookMsg::AcceptOffer { bidder: bidder.to_string() }).unwrap()
+  });
+  match execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), accept("bidder2")) {
+    Err(ContractError::Std(_)) => {}
+    _ => panic!("Must return not found error"),
+  }
+  let mut later = env.clone();
+  later.block.time = later.block.time.plus_seconds(250);
+  match execute(deps.as_mut(), later, mock_info("collection1", &[]), accept("bidder1")) {
+    Err(ContractError::BidExpired {}) => {}
+    _ => panic!("Must return bid expired error"),
+  }
+
+  let res = execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), accept("bidder1")).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "admin1".to_string(), amount: coins(70, "ujuno") }));
+  assert_eq!(res.messages[2].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "owner1".to_string(), amount: coins(900, "ujuno") }));
+  assert_eq!(res.messages[3].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "collection1".to_string(),
+    msg: to_json_binary(&Cw721ExecuteMsg::TransferNft{ recipient: "bidder1".to_string(), token_id: "Hope.1".to_string() }).unwrap(),
+    funds: vec![] }));
+  let sale = res.events.iter().find(|event| event.ty == "sale").unwrap();
+  assert!(sale.attributes.iter().any(|attr| attr.key == "sale_type" && attr.value == "offer"));
+  assert!(query_offer(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), "bidder1".to_string()).unwrap().offer.is_none());
+}
+
+#[test]
+fn sell_into_collection_bid(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+
+  collection_bid_nft_with_coin(deps.as_mut(), env.clone(), "collection1".to_string(), None, "collection_bider1", "ujuno".to_string(), Uint128::new(3000)).unwrap();
+
+  //accepting by message needs the token escrowed in an ask
+  let msg = ExecuteMsg::AcceptCollectionBid { nft_address: "collection1".to_string(), token_id: "Hope.1".to_string(), bidder: "collection_bider1".to_string() };
+  match execute(deps.as_mut(), env.clone(), mock_info("owner1", &[]), msg) {
+    Err(ContractError::NoSuchAsk {}) => {}
+    _ => panic!("Must return no such ask error"),
+  }
+
+  //owners sell an unlisted token into the bid in a single send
+  let accept = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{
+    sender: "owner1".to_string(),
+    token_id: "Hope.1".to_string(),
+    msg: to_json_binary(&NftHookMsg::AcceptCollectionBid { bidder: "collection_bider1".to_string() }).unwrap()
+  });
+  let mut later = env.clone();
+  later.block.time = later.block.time.plus_seconds(400);
+  match execute(deps.as_mut(), later, mock_info("collection1", &[]), accept.clone()) {
+    Err(ContractError::BidExpired {}) => {}
+    _ => panic!("Must return bid expired error"),
+  }
+
+  let res = execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), accept.clone()).unwrap();
+  assert_eq!(res.messages.len(), 4);
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "admin1".to_string(), amount: coins(210, "ujuno") }));
+  assert_eq!(res.messages[2].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "owner1".to_string(), amount: coins(2700, "ujuno") }));
+  assert_eq!(res.messages[3].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "collection1".to_string(),
+    msg: to_json_binary(&Cw721ExecuteMsg::TransferNft{ recipient: "collection_bider1".to_string(), token_id: "Hope.1".to_string() }).unwrap(),
+    funds: vec![] }));
+  assert!(query_collection_bid(deps.as_ref(), "collection1".to_string(), "collection_bider1".to_string()).unwrap().bid.is_none());
+  assert_eq!(query_sale_history_by_token_id(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string(), None, None).unwrap().sale_history.len(), 1);
+
+  //the bid is filled only once
+  assert!(execute(deps.as_mut(), env.clone(), mock_info("collection1", &[]), accept).is_err());
+}
+
+#[test]
+fn cw1155_orders(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "game1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+  add_token(deps.as_mut(), env.clone(), "hope".to_string(), "hope_address".to_string());
+
+  let set_standard = ExecuteMsg::SetNftStandard { nft_address: "game1".to_string(), standard: NftStandard::Cw1155 };
+  match execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), set_standard.clone()) {
+    Err(ContractError::Unauthorized {}) => {}
+    _ => panic!("Must return unauthorized error"),
+  }
+  execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), set_standard).unwrap();
+
+  //cw1155 hooks share the `receive` variant with cw20 and are told apart by their fields
+  let cw1155_send = |from: &str, amount: u128, hook_msg: &Cw1155HookMsg| ExecuteMsg::Receive(ReceiveMsg::Cw1155(Cw1155ReceiveMsg{
+    operator: from.to_string(),
+    from: Some(from.to_string()),
+    token_id: "sword".to_string(),
+    amount: Uint128::new(amount),
+    msg: to_json_binary(hook_msg).unwrap()
+  }));
+  let sell = Cw1155HookMsg::Sell {
+    unit_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(100) },
+    token_address: None,
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300)
+  };
+  let raw: ExecuteMsg = from_json(to_json_binary(&cw1155_send("seller1", 10, &sell)).unwrap()).unwrap();
+  assert_eq!(raw, cw1155_send("seller1", 10, &sell));
+
+  for (hook_type, address) in [(HookType::Ask, "ask_hook"), (HookType::Bid, "bid_hook")] {
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), ExecuteMsg::AddHook { hook_type, address: address.to_string() }).unwrap();
+  }
+
+  execute(deps.as_mut(), env.clone(), mock_info("game1", &[]), cw1155_send("seller1", 6, &sell)).unwrap();
+  let res = execute(deps.as_mut(), env.clone(), mock_info("game1", &[]), cw1155_send("seller1", 4, &sell)).unwrap();
+  let ask = query_cw1155_asks(deps.as_ref(), "game1".to_string(), "sword".to_string(), None, None).unwrap().asks[0].clone();
+  assert_eq!(ask.quantity, Uint128::new(10));
+  assert_eq!(res.messages, vec![SubMsg::reply_on_error(
+    MarketplaceHookMsg::Cw1155AskHook(Cw1155AskHookMsg { ask }).into_cosmos_msg("ask_hook".to_string()).unwrap(),
+    1
+  ).with_gas_limit(500_000)]);
+
+  //listings need units and a price, and more units keep the listed terms
+  let free = Cw1155HookMsg::Sell {
+    unit_price: Asset { denom: "ujuno".to_string(), amount: Uint128::zero() },
+    token_address: None,
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300)
+  };
+  let cheaper = Cw1155HookMsg::Sell {
+    unit_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(90) },
+    token_address: None,
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300)
+  };
+  for (amount, hook_msg) in [(0, &sell), (1, &free), (1, &cheaper)] {
+    match execute(deps.as_mut(), env.clone(), mock_info("game1", &[]), cw1155_send("seller1", amount, hook_msg)) {
+      Err(ContractError::WrongConfig {}) => {}
+      _ => panic!("Must return wrong config error"),
+    }
+  }
+
+  //cw721 listings of a cw1155 collection are refused
+  let sell_nft_msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg{
+    sender: "seller1".to_string(),
+    token_id: "sword".to_string(),
+    msg: to_json_binary(&SellNft{
+      list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(100) },
+      expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
+      token_address: None,
+      instant_sell: None,
+      reserved_for: None,
+      buy_now_price: None,
+      reserve_price: None,
+      min_bid_increment: None
+    }).unwrap()
+  });
+  match execute(deps.as_mut(), env.clone(), mock_info("game1", &[]), sell_nft_msg) {
+    Err(ContractError::WrongNFTContractError {}) => {}
+    _ => panic!("Must return wrong nft contract error"),
+  }
+
+  //partial fill at the unit price
+  let buy = |quantity: u128| ExecuteMsg::BuyCw1155(BuyCw1155Msg { nft_address: "game1".to_string(), token_id: "sword".to_string(), seller: "seller1".to_string(), quantity: Uint128::new(quantity) });
+  match execute(deps.as_mut(), env.clone(), mock_info("buyer1", &coins(300, "ujuno")), buy(4)) {
+    Err(ContractError::NotEnoughFunds {}) => {}
+    _ => panic!("Must return not enough funds error"),
+  }
+  let res = execute(deps.as_mut(), env.clone(), mock_info("buyer1", &coins(400, "ujuno")), buy(4)).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "admin1".to_string(), amount: coins(28, "ujuno") }));
+  assert_eq!(res.messages[1].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "admin2".to_string(), amount: coins(12, "ujuno") }));
+  assert_eq!(res.messages[2].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "seller1".to_string(), amount: coins(360, "ujuno") }));
+  assert_eq!(res.messages[3].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "game1".to_string(),
+    msg: to_json_binary(&Cw1155ExecuteMsg::SendFrom{ from: MOCK_CONTRACT_ADDR.to_string(), to: "buyer1".to_string(), token_id: "sword".to_string(), value: Uint128::new(4), msg: None }).unwrap(),
+    funds: vec![] }));
+  assert_eq!(query_cw1155_asks(deps.as_ref(), "game1".to_string(), "sword".to_string(), None, None).unwrap().asks[0].quantity, Uint128::new(6));
+
+  //history, stats and candles track the unit price, volumes the total paid
+  let sale = query_sale_history_by_token_id(deps.as_ref(), "game1".to_string(), "sword".to_string(), None, None).unwrap().sale_history;
+  assert_eq!((sale[0].amount, sale[0].quantity), (Uint128::new(100), Some(Uint128::new(4))));
+  let stats = query_collection_stats(deps.as_ref(), env.clone(), "game1".to_string(), "ujuno".to_string()).unwrap();
+  assert_eq!((stats.last_sale_price, stats.volume_24h), (Some(Uint128::new(100)), Uint128::new(400)));
+  let candle = &query_candles(deps.as_ref(), "game1".to_string(), "ujuno".to_string(), CandleInterval::Hour, None, None, None).unwrap().candles[0];
+  assert_eq!((candle.close, candle.volume), (Uint128::new(100), Uint128::new(400)));
+
+  match execute(deps.as_mut(), env.clone(), mock_info("buyer1", &coins(700, "ujuno")), buy(7)) {
+    Err(ContractError::WrongConfig {}) => {}
+    _ => panic!("Must return wrong config error"),
+  }
+
+  //cw20 bid for 5 units at 100 each, filled 2 units by a holder sending them
+  let bid_msg = ExecuteMsg::Receive(ReceiveMsg::Cw20(Cw20ReceiveMsg{
+    sender: "bidder1".to_string(),
+    amount: Uint128::new(500),
+    msg: to_json_binary(&TokenHookMsg::SetCw1155Bid { nft_address: "game1".to_string(), token_id: "sword".to_string(), quantity: Uint128::new(5), expire: Timestamp::from_seconds(env.block.time.seconds() + 300) }).unwrap()
+  }));
+  let res = execute(deps.as_mut(), env.clone(), mock_info("hope_address", &[]), bid_msg).unwrap();
+  let bid = query_cw1155_bids(deps.as_ref(), "game1".to_string(), "sword".to_string(), None, None).unwrap().bids[0].clone();
+  assert_eq!(res.messages.last().unwrap().msg, MarketplaceHookMsg::Cw1155BidHook(Cw1155BidHookMsg { bid }).into_cosmos_msg("bid_hook".to_string()).unwrap());
+  let accept = Cw1155HookMsg::AcceptBid { bidder: "bidder1".to_string() };
+  for amount in [0, 6] {
+    match execute(deps.as_mut(), env.clone(), mock_info("game1", &[]), cw1155_send("holder1", amount, &accept)) {
+      Err(ContractError::WrongConfig {}) => {}
+      _ => panic!("Must return wrong config error"),
+    }
+  }
+  let res = execute(deps.as_mut(), env.clone(), mock_info("game1", &[]), cw1155_send("holder1", 2, &accept)).unwrap();
+  assert_eq!(res.messages[2].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "hope_address".to_string(),
+    msg: to_json_binary(&Cw20ExecuteMsg::Transfer{ recipient: "holder1".to_string(), amount: Uint128::new(180) }).unwrap(),
+    funds: vec![] }));
+  assert_eq!(res.messages[3].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "game1".to_string(),
+    msg: to_json_binary(&Cw1155ExecuteMsg::SendFrom{ from: MOCK_CONTRACT_ADDR.to_string(), to: "bidder1".to_string(), token_id: "sword".to_string(), value: Uint128::new(2), msg: None }).unwrap(),
+    funds: vec![] }));
+
+  //the rest of the bid is refunded and the unsold units returned
+  let remove = ExecuteMsg::RemoveCw1155Bid { nft_address: "game1".to_string(), token_id: "sword".to_string() };
+  let res = execute(deps.as_mut(), env.clone(), mock_info("bidder1", &[]), remove).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "hope_address".to_string(),
+    msg: to_json_binary(&Cw20ExecuteMsg::Transfer{ recipient: "bidder1".to_string(), amount: Uint128::new(300) }).unwrap(),
+    funds: vec![] }));
+  let withdraw = ExecuteMsg::WithdrawCw1155 { nft_address: "game1".to_string(), token_id: "sword".to_string() };
+  let res = execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), withdraw).unwrap();
+  assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "game1".to_string(),
+    msg: to_json_binary(&Cw1155ExecuteMsg::SendFrom{ from: MOCK_CONTRACT_ADDR.to_string(), to: "seller1".to_string(), token_id: "sword".to_string(), value: Uint128::new(6), msg: None }).unwrap(),
+    funds: vec![] }));
+  assert_eq!(query_cw1155_asks(deps.as_ref(), "game1".to_string(), "sword".to_string(), None, None).unwrap().asks.len(), 0);
+  assert_eq!(query_cw1155_bids(deps.as_ref(), "game1".to_string(), "sword".to_string(), None, None).unwrap().bids.len(), 0);
+}
+
+#[test]
+fn ics721_voucher_collections(){
+  let mut deps = mock_dependencies();
+  let env = mock_env();
+
+  //init contract
+  setup_contract(deps.as_mut());
+
+  //add collection
+  add_contract(deps.as_mut(), env.clone() , "voucher1".to_string() );
+  add_contract(deps.as_mut(), env.clone() , "collection1".to_string() );
+
+  //add coin
+  add_coin(deps.as_mut(), env.clone(), "ujuno".to_string());
+
+  //the mocked bridge only instantiated voucher1
+  deps.querier.update_wasm(|query| match query {
+    WasmQuery::Smart { contract_addr, msg } if contract_addr == "ics721_bridge" => {
+      let class_id = match from_json(msg).unwrap() {
+        Ics721QueryMsg::ClassId { contract } if contract == "voucher1" => Some("wasm.ics721_bridge/channel-3/nft-transfer/channel-9/stars/punks".to_string()),
+        _ => None,
+      };
+      SystemResult::Ok(ContractResult::Ok(to_json_binary(&class_id).unwrap()))
+    }
+    _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+  });
+
+  let set_bridge = |collection: &str| ExecuteMsg::SetIcs721Bridge { nft_address: collection.to_string(), bridge: Some("ics721_bridge".to_string()) };
+  match execute(deps.as_mut(), env.clone(), mock_info("seller1", &[]), set_bridge("voucher1")) {
+    Err(ContractError::Unauthorized {}) => {}
+    _ => panic!("Must return unauthorized error"),
+  }
+  match execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), set_bridge("collection1")) {
+    Err(ContractError::WrongCollection {}) => {}
+    _ => panic!("Must return wrong collection error"),
+  }
+  execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), set_bridge("voucher1")).unwrap();
+
+  //ask queries carry the origin class and trace
+  sell_nft(deps.as_mut(), env.clone(), "voucher1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Punk.1".to_string());
+  sell_nft(deps.as_mut(), env.clone(), "collection1", "seller1".to_string(), "ujuno".to_string(), Uint128::new(1000), None, "Hope.1".to_string());
+  let origin = query_ask(deps.as_ref(), "voucher1".to_string(), "Punk.1".to_string()).unwrap().origin.unwrap();
+  assert_eq!(origin.port_id, "wasm.ics721_bridge");
+  assert_eq!(origin.channel_id, "channel-3");
+  assert_eq!(origin.trace, vec!["wasm.ics721_bridge/channel-3".to_string(), "nft-transfer/channel-9".to_string()]);
+  assert_eq!(origin.origin_class_id, "stars/punks");
+  assert!(query_ask(deps.as_ref(), "collection1".to_string(), "Hope.1".to_string()).unwrap().origin.is_none());
+  let asks = query_asks_by_seller(deps.as_ref(), "seller1".to_string(), None, None).unwrap();
+  assert_eq!(asks.asks.len(), 2);
+  assert_eq!(asks.origins.len(), 1);
+  assert_eq!(asks.origins[0].collection, "voucher1");
+
+  //buyers can have the NFT sent back to the origin chain
+  let buy = |collection: &str, token_id: &str| ExecuteMsg::SetBidCoin {
+    nft_address: collection.to_string(),
+    expire: Timestamp::from_seconds(env.block.time.seconds() + 300),
+    sale_type: SaleType::FixedPrice,
+    token_id: Some(token_id.to_string()),
+    list_price: Asset { denom: "ujuno".to_string(), amount: Uint128::new(1000) },
+    finder: None,
+    origin_receiver: Some("stars1receiver".to_string())
+  };
+  match execute(deps.as_mut(), env.clone(), mock_info("buyer1", &coins(1000, "ujuno")), buy("collection1", "Hope.1")) {
+    Err(ContractError::WrongConfig {}) => {}
+    _ => panic!("Must return wrong config error"),
+  }
+  let res = execute(deps.as_mut(), env.clone(), mock_info("buyer1", &coins(1000, "ujuno")), buy("voucher1", "Punk.1")).unwrap();
+  assert_eq!(res.messages[2].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "seller1".to_string(), amount: coins(900, "ujuno") }));
+  assert_eq!(res.messages[3].msg, CosmosMsg::Wasm(WasmMsg::Execute{
+    contract_addr: "voucher1".to_string(),
+    msg: to_json_binary(&Cw721ExecuteMsg::SendNft{
+      contract: "ics721_bridge".to_string(),
+      token_id: "Punk.1".to_string(),
+      msg: to_json_binary(&Ics721OutgoingMsg{
+        receiver: "stars1receiver".to_string(),
+        channel_id: "channel-3".to_string(),
+        timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(600)),
+        memo: None
+      }).unwrap()
+    }).unwrap(),
+    funds: vec![] }));
+}